@@ -0,0 +1,234 @@
+//! Where and how to write the CSS from `css!` calls, from `[package.metadata.css-rs]` in
+//! the crate's Cargo.toml or `[workspace.metadata.css-rs]` in the workspace's.
+//!
+//! ```toml
+//! [package.metadata.css-rs]
+//! # Relative to this Cargo.toml. The OUTPUT_CSS environment variable takes precedence.
+//! output = "build/app.css"
+//! minify = true
+//! # "none", "crate" or "module"
+//! split = "crate"
+//! # Writes build/app.manifest.json
+//! manifest = true
+//! # css! returns a `css_rs::Css` that injects its CSS at runtime
+//! runtime = true
+//! ```
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How the CSS should be written
+#[derive(Debug, Default, PartialEq)]
+pub struct Config {
+    /// The stylesheet with all of the CSS. Nothing gets written without one.
+    pub output: Option<PathBuf>,
+    /// Leave out whitespace that browsers don't need
+    pub minify: bool,
+    /// Also write the CSS into a file for every crate or module
+    pub split: Split,
+    /// Write a JSON file that maps every module with CSS to its CSS file and classes
+    pub manifest: bool,
+    /// css! returns a `css_rs::Css` that carries its CSS instead of a `&'static str`, for
+    /// crates that depend on `css-rs`
+    pub runtime: bool,
+}
+
+/// Which separate files to write the CSS into, next to the output file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Split {
+    /// Only the output file
+    None,
+    /// `app.my_crate.css`
+    Crate,
+    /// `app.my_crate.views.nav_bar.css`
+    Module,
+}
+
+impl Default for Split {
+    fn default() -> Split {
+        Split::None
+    }
+}
+
+/// The config for the crate that is being compiled.
+pub fn load() -> Result<Config, String> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+
+    let mut config = find_config(&manifest_dir)?.unwrap_or_default();
+
+    if let Ok(output_css) = env::var("OUTPUT_CSS") {
+        config.output = Some(PathBuf::from(output_css));
+    }
+
+    Ok(config)
+}
+
+/// Our crate's metadata, or else the workspace's
+fn find_config(manifest_dir: &Path) -> Result<Option<Config>, String> {
+    let manifest = manifest_dir.join("Cargo.toml");
+    if let Ok(toml) = fs::read_to_string(&manifest) {
+        if let Some(config) = parse(&toml, "package.metadata.css-rs", manifest_dir)? {
+            return Ok(Some(config));
+        }
+    }
+
+    for dir in manifest_dir.ancestors() {
+        let toml = match fs::read_to_string(dir.join("Cargo.toml")) {
+            Ok(toml) => toml,
+            Err(_) => continue,
+        };
+
+        if toml.lines().any(|line| line.trim() == "[workspace]") {
+            return parse(&toml, "workspace.metadata.css-rs", dir);
+        }
+    }
+
+    Ok(None)
+}
+
+/// Read the `[table]` from a Cargo.toml. We only need strings and booleans, so this isn't a
+/// full TOML parser.
+fn parse(toml: &str, table: &str, dir: &Path) -> Result<Option<Config>, String> {
+    let header = format!("[{}]", table);
+
+    let mut lines = toml.lines().skip_while(|line| line.trim() != header);
+    if lines.next().is_none() {
+        return Ok(None);
+    }
+
+    let mut config = Config::default();
+
+    for line in lines {
+        let line = line.trim();
+        if line.starts_with('[') {
+            break;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut key_value = line.splitn(2, '=');
+        let key = key_value.next().unwrap_or("").trim();
+        let value = parse_value(key_value.next().unwrap_or("").trim())
+            .ok_or_else(|| format!("Could not read `{}` in [{}]", line, table))?;
+
+        let expected = |kind| format!("`{}` in [{}] should be {}", key, table, kind);
+
+        match (key, value) {
+            ("output", Value::String(output)) => config.output = Some(dir.join(output)),
+            ("minify", Value::Bool(minify)) => config.minify = minify,
+            ("manifest", Value::Bool(manifest)) => config.manifest = manifest,
+            ("runtime", Value::Bool(runtime)) => config.runtime = runtime,
+            ("split", Value::String(split)) => {
+                config.split = match split.as_str() {
+                    "none" => Split::None,
+                    "crate" => Split::Crate,
+                    "module" => Split::Module,
+                    _ => return Err(expected(r#""none", "crate" or "module""#)),
+                };
+            }
+            ("output", _) => return Err(expected("a path")),
+            ("split", _) => return Err(expected("a string")),
+            ("minify", _) | ("manifest", _) | ("runtime", _) => {
+                return Err(expected("true or false"));
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown key `{}` in [{}]. Expected output, minify, split, manifest or \
+                     runtime.",
+                    key, table
+                ));
+            }
+        }
+    }
+
+    Ok(Some(config))
+}
+
+enum Value {
+    String(String),
+    Bool(bool),
+}
+
+/// `"build/app.css" # a comment` or `true`
+fn parse_value(value: &str) -> Option<Value> {
+    if value.starts_with('"') {
+        let end = value[1..].find('"')?;
+        return Some(Value::String(value[1..1 + end].to_string()));
+    }
+
+    match value.split('#').next()?.trim() {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_metadata() {
+        let toml = r#"
+[package]
+name = "app"
+
+[package.metadata.css-rs]
+output = "build/app.css" # where our CSS goes
+minify = true
+split = "module"
+runtime = true
+
+[dependencies]
+minify = "not ours"
+"#;
+
+        assert_eq!(
+            parse(toml, "package.metadata.css-rs", Path::new("/app")),
+            Ok(Some(Config {
+                output: Some(PathBuf::from("/app/build/app.css")),
+                minify: true,
+                split: Split::Module,
+                manifest: false,
+                runtime: true,
+            }))
+        );
+
+        assert_eq!(
+            parse(toml, "workspace.metadata.css-rs", Path::new("/app")),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn invalid_metadata() {
+        let parse = |metadata: &str| {
+            let toml = format!("[package.metadata.css-rs]\n{}", metadata);
+            parse(&toml, "package.metadata.css-rs", Path::new("/app"))
+        };
+
+        assert_eq!(
+            parse(r#"split = "file""#),
+            Err(
+                r#"`split` in [package.metadata.css-rs] should be "none", "crate" or "module""#
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse("minify = 1"),
+            Err("Could not read `minify = 1` in [package.metadata.css-rs]".to_string())
+        );
+        assert_eq!(
+            parse("minfy = true"),
+            Err(
+                "Unknown key `minfy` in [package.metadata.css-rs]. Expected output, minify, \
+                 split, manifest or runtime."
+                    .to_string()
+            )
+        );
+    }
+}
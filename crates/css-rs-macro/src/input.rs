@@ -0,0 +1,174 @@
+//! The string literals passed to `css!`, and finding where an offset into their CSS is in
+//! the source code.
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use syn::LitStr;
+
+/// The CSS from all of the string literals in a `css!` call
+pub struct CssInput {
+    /// The literals' contents, one after the other
+    pub css: String,
+    /// Whether the call starts with `module`, which makes every class local to it
+    pub module: bool,
+    literals: Vec<CssLiteral>,
+}
+
+struct CssLiteral {
+    literal: Literal,
+    /// Where this literal's contents start in the combined CSS
+    start: usize,
+    /// How long this literal's contents are
+    len: usize,
+    /// How many characters come before the contents in the source code, such as 3 for `r#"`
+    prefix_len: usize,
+    /// Escapes such as `\n` mean that the contents don't line up with the source code, so
+    /// we can only point at the entire literal
+    lines_up: bool,
+}
+
+impl CssInput {
+    /// css!{" :host { ... } "}, css!{r#" :host { ... } "#} or several literals such as
+    /// css!{":host { " "color: red; " "}"}, optionally after `module`, such as
+    /// css!{module " .list { ... } "}
+    pub fn parse(input: TokenStream) -> Result<CssInput, TokenStream> {
+        let mut tokens = input.into_iter().peekable();
+
+        let module = match tokens.peek() {
+            Some(TokenTree::Ident(ident)) => ident.to_string() == "module",
+            _ => false,
+        };
+        if module {
+            tokens.next();
+        }
+
+        let mut input = CssInput::parse_literals(tokens.collect())?;
+        input.module = module;
+
+        if input.literals.is_empty() {
+            return Err(expects_string_literals(Span::call_site()));
+        }
+
+        Ok(input)
+    }
+
+    fn parse_literals(input: TokenStream) -> Result<CssInput, TokenStream> {
+        let mut css = String::new();
+        let mut literals = vec![];
+
+        for token in input {
+            let literal = match token {
+                TokenTree::Literal(literal) => literal,
+                // Macros that call css! can end up wrapping our literals in invisible groups
+                TokenTree::Group(ref group) if group.delimiter() == Delimiter::None => {
+                    let inner = CssInput::parse_literals(group.stream())?;
+                    for mut literal in inner.literals {
+                        literal.start += css.len();
+                        literals.push(literal);
+                    }
+                    css.push_str(&inner.css);
+                    continue;
+                }
+                token => return Err(expects_string_literals(token.span())),
+            };
+
+            let value = match syn::parse::<LitStr>(TokenTree::Literal(literal.clone()).into()) {
+                Ok(lit) => lit.value(),
+                Err(_) => return Err(expects_string_literals(literal.span())),
+            };
+
+            let source = literal.to_string();
+            let prefix_len = source.find('"').unwrap_or(0) + 1;
+
+            literals.push(CssLiteral {
+                start: css.len(),
+                len: value.len(),
+                prefix_len,
+                lines_up: source.starts_with('r') || !source.contains('\\'),
+                literal,
+            });
+            css.push_str(&value);
+        }
+
+        Ok(CssInput {
+            css,
+            module: false,
+            literals,
+        })
+    }
+
+    /// The span of the `len` bytes at `offset` in the CSS, or of the whole literal that they
+    /// are in if we can't be that precise. A `len` of 0 means the character at `offset`.
+    pub fn span_at(&self, offset: usize, len: usize) -> Span {
+        let css_literal = self
+            .literals
+            .iter()
+            .find(|literal| offset < literal.start + literal.len)
+            .unwrap_or_else(|| self.literals.last().unwrap());
+
+        if css_literal.lines_up {
+            let offset_in_literal = offset.saturating_sub(css_literal.start);
+            let len = match len {
+                0 => self.css[offset.min(self.css.len())..]
+                    .chars()
+                    .next()
+                    .map(|c| c.len_utf8())
+                    .unwrap_or(0),
+                len => len,
+            };
+
+            let start = css_literal.prefix_len + offset_in_literal;
+            if let Some(span) = css_literal.literal.subspan(start..start + len) {
+                return span;
+            }
+        }
+
+        css_literal.literal.span()
+    }
+
+    /// A `compile_error!` pointing at `offset` in the CSS
+    pub fn compile_error(&self, offset: usize, message: &str) -> TokenStream {
+        let (line, column) = line_and_column(&self.css, offset);
+        let message = format!("{} (line {}, column {} of the CSS)", message, line, column);
+
+        compile_error(self.span_at(offset, 0), &message)
+    }
+}
+
+/// 1 based line and column numbers
+pub fn line_and_column(css: &str, offset: usize) -> (usize, usize) {
+    let before = &css[..offset.min(css.len())];
+
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+
+    (line, column)
+}
+
+fn expects_string_literals(span: Span) -> TokenStream {
+    compile_error(
+        span,
+        r#"css! expects one or more strings, i.e. css!{":host { color: red; }"}"#,
+    )
+}
+
+/// compile_error!("message") with every token spanned to `span` so that the error shows up
+/// there
+pub fn compile_error(span: Span, message: &str) -> TokenStream {
+    let mut message = Literal::string(message);
+    message.set_span(span);
+
+    let mut args = Group::new(Delimiter::Parenthesis, TokenTree::Literal(message).into());
+    args.set_span(span);
+
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+
+    vec![
+        TokenTree::Ident(Ident::new("compile_error", span)),
+        TokenTree::Punct(bang),
+        TokenTree::Group(args),
+    ]
+    .into_iter()
+    .collect()
+}
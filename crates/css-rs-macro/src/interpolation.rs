@@ -0,0 +1,251 @@
+//! Rust values in CSS, such as `color: {theme::PRIMARY};`.
+//!
+//! Every interpolation becomes a custom property, `color: var(--_css_rs_5e1c2b0a-0);`, that
+//! gets its value from the `style` attribute of the element. So both constants and values
+//! that differ for every instance of a component work.
+
+use parser::{AtRule, Item, ParseError, Stylesheet};
+
+/// Stands in for the `{` of an interpolation while the CSS is parsed, so that the parser
+/// treats the interpolation as text. It's one byte, like `{`, so offsets stay the same.
+const START: char = '\u{1}';
+/// Stands in for the `}` of an interpolation
+const END: char = '\u{2}';
+
+/// `{theme::PRIMARY}` in the CSS
+#[derive(Debug, PartialEq)]
+pub struct Interpolation {
+    /// `theme::PRIMARY`
+    pub expr: String,
+    /// Where the expression starts in the CSS
+    pub offset: usize,
+}
+
+/// Find the interpolations in the CSS and mark them so that they can be parsed as text.
+///
+/// An interpolation is a Rust path or field, such as `{theme::PRIMARY}` or `{self.width}`,
+/// in braces. Anything else in braces is a CSS block.
+pub fn mark_interpolations(css: &str) -> (String, Vec<Interpolation>) {
+    let mut marked = String::with_capacity(css.len());
+    let mut interpolations = vec![];
+    let mut quote = None;
+    let mut idx = 0;
+
+    while let Some(c) = css[idx..].chars().next() {
+        match (c, quote) {
+            (_, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('"', None) | ('\'', None) => quote = Some(c),
+            ('/', None) if css[idx..].starts_with("/*") => {
+                let end = css[idx + 2..]
+                    .find("*/")
+                    .map(|end| idx + 2 + end + 2)
+                    .unwrap_or(css.len());
+                marked.push_str(&css[idx..end]);
+                idx = end;
+                continue;
+            }
+            ('{', None) => {
+                if let Some(len) = interpolation_len(&css[idx + 1..]) {
+                    let expr = &css[idx + 1..idx + 1 + len];
+
+                    interpolations.push(Interpolation {
+                        expr: expr.trim().to_string(),
+                        offset: idx + 1 + (expr.len() - expr.trim_start().len()),
+                    });
+
+                    marked.push(START);
+                    marked.push_str(expr);
+                    marked.push(END);
+                    idx += len + 2;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        marked.push(c);
+        idx += c.len_utf8();
+    }
+
+    (marked, interpolations)
+}
+
+/// How long the expression is if this is the rest of an interpolation, such as
+/// `theme::PRIMARY} ...`
+fn interpolation_len(rest: &str) -> Option<usize> {
+    let end = rest.find('}')?;
+    let expr = rest[..end].trim();
+
+    // `theme::PRIMARY` or `self.width`, but not a block such as `{color:red}`
+    let is_ident = |ident: &str| {
+        ident
+            .chars()
+            .next()
+            .map(|c| c.is_alphabetic() || c == '_')
+            .unwrap_or(false)
+            && ident.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    let is_path = expr
+        .split("::")
+        .all(|segment| segment.split('.').all(is_ident));
+
+    if is_path {
+        Some(end)
+    } else {
+        None
+    }
+}
+
+/// The expressions that need a custom property, without duplicates, in the order that they
+/// first appear
+pub fn unique_exprs(interpolations: &[Interpolation]) -> Vec<String> {
+    let mut exprs: Vec<String> = vec![];
+
+    for interpolation in interpolations {
+        if !exprs.iter().any(|expr| *expr == interpolation.expr) {
+            exprs.push(interpolation.expr.clone());
+        }
+    }
+
+    exprs
+}
+
+/// The custom property for the interpolated expression at `idx` in `unique_exprs`
+pub fn custom_property(class: &str, idx: usize) -> String {
+    format!("--{}-{}", class, idx)
+}
+
+/// Replace the marked interpolations in a value with the custom properties that they use.
+///
+/// A unit right after an interpolation, such as `{width}px`, gets multiplied in with `calc`
+/// since `var(--width)px` is not valid CSS.
+pub fn replace_interpolations(value: &str, class: &str, exprs: &[String]) -> String {
+    let mut replaced = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find(START) {
+        replaced.push_str(&rest[..start]);
+
+        let end = start + rest[start..].find(END).expect("Interpolations are closed");
+        let expr = rest[start + 1..end].trim();
+        let idx = exprs.iter().position(|e| e == expr).unwrap_or(0);
+        let var = format!("var({})", custom_property(class, idx));
+
+        rest = &rest[end + 1..];
+
+        let unit_len = rest
+            .find(|c: char| !(c.is_alphabetic() || c == '%'))
+            .unwrap_or(rest.len());
+        match unit_len {
+            0 => replaced.push_str(&var),
+            _ => {
+                replaced.push_str(&format!("calc({} * 1{})", var, &rest[..unit_len]));
+                rest = &rest[unit_len..];
+            }
+        }
+    }
+
+    replaced.push_str(rest);
+    replaced
+}
+
+/// Interpolations can only be used in property values.
+pub fn check_placement(stylesheet: &Stylesheet) -> Result<(), ParseError> {
+    check_items(&stylesheet.items)
+}
+
+fn check_items(items: &[Item]) -> Result<(), ParseError> {
+    let misplaced = |offset| ParseError {
+        message: "Rust values such as `{theme::PRIMARY}` can only be used in property values"
+            .to_string(),
+        offset,
+    };
+
+    for item in items {
+        match item {
+            Item::Rule(rule) => {
+                if rule.selector.contains(START) {
+                    return Err(misplaced(rule.offset));
+                }
+                check_items(&rule.items)?;
+            }
+            Item::AtRule(at_rule) => {
+                if at_rule.prelude.contains(START) {
+                    return Err(misplaced(at_rule.offset));
+                }
+                if let AtRule {
+                    block: Some(items), ..
+                } = at_rule
+                {
+                    check_items(items)?;
+                }
+            }
+            Item::Declaration(declaration) => {
+                if declaration.property.contains(START) {
+                    return Err(misplaced(declaration.offset));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser;
+
+    #[test]
+    fn mark_interpolations() {
+        let css = r#":host { color: { theme::PRIMARY }; content: "{not_me}"; } /* {or_me} */
+        :host { div{color:red} }"#;
+
+        let (marked, interpolations) = super::mark_interpolations(css);
+
+        assert_eq!(marked.len(), css.len());
+        assert_eq!(
+            interpolations,
+            vec![Interpolation {
+                expr: "theme::PRIMARY".to_string(),
+                offset: css.find("theme").unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn interpolations_become_custom_properties() {
+        let css = ":host { color: {theme::PRIMARY}; margin: {gap}px {gap}%; } :host > div { width: {width}; }";
+
+        let (marked, interpolations) = super::mark_interpolations(css);
+        let stylesheet = parser::parse(&marked).unwrap();
+        let exprs = unique_exprs(&interpolations);
+
+        assert_eq!(exprs, vec!["theme::PRIMARY", "gap", "width"]);
+        assert_eq!(
+            stylesheet.to_css("app", &exprs),
+            r#".app {
+    color: var(--app-0);
+    margin: calc(var(--app-1) * 1px) calc(var(--app-1) * 1%);
+}
+.app > div {
+    width: var(--app-2);
+}
+"#
+        );
+    }
+
+    #[test]
+    fn interpolations_are_only_for_values() {
+        let css = ":host { color: red; } {parent} > div { color: {color}; }";
+
+        let (marked, _) = super::mark_interpolations(css);
+        let stylesheet = parser::parse(&marked).unwrap();
+
+        assert_eq!(
+            check_placement(&stylesheet).unwrap_err().offset,
+            css.find("{parent}").unwrap()
+        );
+    }
+}
@@ -2,6 +2,9 @@
 //!
 //! github.com/chinedufn/percy/examples/css-in-rust
 
+#![feature(proc_macro_span)]
+#![cfg_attr(feature = "warn-unknown-properties", feature(proc_macro_diagnostic))]
+
 #[feature(proc_macro)]
 #[macro_use]
 extern crate syn;
@@ -10,28 +13,33 @@ extern crate quote;
 #[macro_use]
 extern crate lazy_static;
 extern crate proc_macro;
-
-use syn::Expr;
+extern crate proc_macro2;
 
 use proc_macro::TokenStream;
-use proc_macro::TokenTree;
+use proc_macro2::Span;
+use syn::Ident;
 
 use std::env;
-use std::fs;
-use std::fs::File;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::Path;
-use std::sync::Mutex;
-
-lazy_static! {
-    static ref CSS_COUNTER: Mutex<u32> = Mutex::new(0);
-}
+use std::path::{Path, PathBuf};
+
+use input::CssInput;
 
-/// Parses the syntax for writing inline css. Every call to css! will have its class
-/// name incremented by one.
+mod config;
+mod input;
+mod interpolation;
+mod nesting;
+mod output;
+mod parser;
+#[cfg(feature = "warn-unknown-properties")]
+mod properties;
+mod scope;
+
+/// Parses the syntax for writing inline css. Every call to css! gets a class name that is
+/// created from a hash of its CSS and the crate and module that it is in, such as
+/// "_css_rs_5e1c2b0a".
 ///
-/// So your first css! call is class "._css_rs_0", then "._css_rs_1", etc.
+/// So a class name only changes when its CSS or module does, no matter what order the compiler
+/// expands your css! calls in or which crates get recompiled.
 ///
 /// To write your css to a file use:
 ///
@@ -39,6 +47,56 @@ lazy_static! {
 /// OUTPUT_CSS=/path/to/my/output.css cargo run my-app
 /// ```
 ///
+/// or configure it in your Cargo.toml, where the output can also be minified and split into a
+/// file per crate or per module with a manifest of which module's CSS is in which file:
+///
+/// ```toml
+/// [package.metadata.css-rs]
+/// output = "build/app.css"
+/// minify = true
+/// split = "module"
+/// manifest = true
+/// ```
+///
+/// Use `[workspace.metadata.css-rs]` in your workspace's Cargo.toml to configure every crate
+/// at once. Rules are sorted by crate, module and class, and rules that show up more than once
+/// are only written once.
+///
+/// The CSS of each crate target is also kept in a `/path/to/my/output.css.fragments`
+/// directory, which is used to recreate the output file whenever a crate is recompiled.
+/// Fragments only ever get CSS added to them, so delete the directory to clear out CSS from
+/// css! calls that you've changed or removed.
+///
+/// The CSS is parsed at compile time, so unbalanced braces, unclosed strings and comments and
+/// declarations without a value are compile errors that point into your string. Enable the
+/// `warn-unknown-properties` feature to also get warnings about misspelled property names.
+///
+/// Rules can be nested inside of other rules, where `&` refers to the parent selector, and
+/// they get flattened into plain CSS.
+///
+/// With `runtime = true` in a crate's `[package.metadata.css-rs]` its css! calls return a
+/// `css_rs::Css` instead of a `&'static str`, which carries the CSS along with the class name
+/// and injects it the first time that it gets rendered. See the `css-rs` crate.
+///
+/// Rust values can be used in property values, such as `color: {theme::PRIMARY};`. Each one
+/// becomes a custom property, and css! returns a struct with a `class` and a `style` that
+/// sets them, to use as the element's style attribute.
+///
+/// Long stylesheets can be split across several strings, which get joined together.
+///
+/// Starting the call with `module` makes every class in the CSS local to it, like in CSS
+/// modules, and css! returns a struct with a field for each class's generated name. Names of
+/// `@keyframes` are always local.
+///
+/// ```ignore
+/// let classes = css! {module "
+///     .list { display: flex; }
+///     .list-item:hover { color: blue; }
+/// "};
+///
+/// html! { <ul class=classes.list> <li class=classes.list_item></li> </ul> }
+/// ```
+///
 /// # Examples
 ///
 /// ```ignore
@@ -53,11 +111,15 @@ lazy_static! {
 ///       "
 ///       :host {
 ///         background-color: red;
-///       }
 ///
-///       :host > div {
-///         display: flex;
-///         align-items: center;
+///         > div {
+///           display: flex;
+///           align-items: center;
+///         }
+///
+///         &:hover {
+///           background-color: blue;
+///         }
 ///       }
 ///       "
 ///     };
@@ -66,70 +128,337 @@ lazy_static! {
 ///         :host { display: flex; }
 ///     "#};
 ///
-///     assert_eq!(class1, "_css_rs_0".to_string());
-///     assert_eq!(class2, "_css_rs_1".to_string());
+///     let class3 = css! {
+///         ":host { display: flex; }"
+///         ":host > span { color: blue; }"
+///     };
+///
+///     assert!(class1.starts_with("_css_rs_"));
+///     assert_ne!(class1, class2);
+///     assert_ne!(class2, class3);
 /// }
 /// ```
 #[proc_macro]
 pub fn css(input: TokenStream) -> TokenStream {
-    let mut css_counter = CSS_COUNTER.lock().unwrap();
+    // Handles:
+    //   css!{r#" :host { ... } "#}
+    //     as well as
+    //   css!{" :host { ... } "}
+    //     as well as several strings, which get joined together
+    let input = match CssInput::parse(input) {
+        Ok(input) => input,
+        Err(compile_error) => return compile_error,
+    };
 
-    let class = format!("_css_rs_{}", css_counter);
+    let (css, interpolations) = interpolation::mark_interpolations(&input.css);
 
-    let css_file = env::vars().find(|(key, _)| key == "OUTPUT_CSS");
+    let mut stylesheet = match parser::parse(&css) {
+        Ok(stylesheet) => stylesheet,
+        Err(err) => return input.compile_error(err.offset, &err.message),
+    };
+    stylesheet.module = input.module;
+    if let Err(err) = interpolation::check_placement(&stylesheet) {
+        return input.compile_error(err.offset, &err.message);
+    }
 
-    if css_file.is_some() {
-        let css_file = css_file.unwrap().1;
+    #[cfg(feature = "warn-unknown-properties")]
+    warn_unknown_properties(&input, &stylesheet);
 
-        if *css_counter == 0 {
-            if Path::new(&css_file).exists() {
-                fs::remove_file(&css_file).unwrap();
-            }
+    let config = match config::load() {
+        Ok(config) => config,
+        Err(err) => return input::compile_error(proc_macro::Span::call_site(), &err),
+    };
 
-            let mut css_file = OpenOptions::new()
-                .write(true)
-                .create_new(true)
-                .open(css_file)
-                .unwrap();
+    let crate_name = crate_name();
+    let module = module_name();
+    // The same CSS with and without `module` gets scoped differently
+    let class = if input.module {
+        class_name(&crate_name, &module, &format!("module {}", input.css))
+    } else {
+        class_name(&crate_name, &module, &input.css)
+    };
+    let exprs = interpolation::unique_exprs(&interpolations);
 
-            write_css_to_file(&mut css_file, &class, input);
-        } else {
-            let mut css_file = OpenOptions::new().append(true).open(css_file).unwrap();
+    if let Err(err) = scope::check_module(&stylesheet, !exprs.is_empty()) {
+        return input.compile_error(err.offset, &err.message);
+    }
 
-            write_css_to_file(&mut css_file, &class, input);
-        }
+    // Replace :host selectors with the class name of the :host element
+    // A fake shadow-dom implementation.. if you will..
+    let rules = stylesheet.rules(&class, &exprs, config.minify);
+
+    if let Some(output_css) = &config.output {
+        output::write_css(
+            &config,
+            output_css,
+            &target_name(),
+            &crate_name,
+            &module,
+            &class,
+            &rules,
+        );
     }
 
-    *css_counter += 1;
+    let css = rules.concat();
 
-    let expanded = quote! {
-    #class
+    if !stylesheet.module && exprs.is_empty() {
+        return class_value(config.runtime, &class, &class, &css).into();
+    }
+
+    let mut fields = vec![];
+    let mut field_types = vec![];
+    let mut values = vec![];
+
+    if !stylesheet.module {
+        fields.push(Ident::new("class", Span::call_site()));
+        field_types.push(class_type(config.runtime));
+        values.push(class_value(config.runtime, &class, &class, &css));
+    }
+
+    // In a `module` every class is local, so we return a struct with a field for each one,
+    // such as `classes.list_item` for `.list-item`
+    let scope = scope::Scope::new(&class, &stylesheet, &exprs);
+    for (local_class, _) in scope::local_classes(&stylesheet).iter() {
+        fields.push(Ident::new(
+            &scope::field_name(local_class),
+            Span::call_site(),
+        ));
+        field_types.push(class_type(config.runtime));
+        values.push(class_value(
+            config.runtime,
+            &scope.local_name(local_class),
+            &class,
+            &css,
+        ));
+    }
+
+    // Rust values such as `{theme::PRIMARY}` get set as custom properties through the
+    // element's style attribute, i.e. `<div class=card.class style=card.style>`
+    let derive_copy = if exprs.is_empty() {
+        quote! { Copy, }
+    } else {
+        fields.push(Ident::new("style", Span::call_site()));
+        field_types.push(quote! { String });
+        values.push(style_value(&input, &class, &interpolations, &exprs));
+
+        quote! {}
     };
 
+    let field_names = fields.clone();
+
+    let expanded = quote! {{
+        #[derive(Debug, Clone, #derive_copy PartialEq)]
+        struct CssClasses {
+            #(pub #fields: #field_types,)*
+        }
+
+        CssClasses {
+            #(#field_names: #values,)*
+        }
+    }};
+
     expanded.into()
 }
 
-fn write_css_to_file(css_file: &mut File, class: &str, input: TokenStream) {
-    for css in input.into_iter() {
-        let mut css = css.to_string();
-
-        // Remove the surrounding quotes from so that we can write only the
-        // CSS to our file.
-        //
-        // Handles:
-        //   css!{r#" :host { ... } "#}
-        //     as well as
-        //   css!{" :host { ... } "}
-        let first_quote_mark = css.find(r#"""#).unwrap();
-        let last_quote_mark = css.rfind(r#"""#).unwrap();
-        css.truncate(last_quote_mark);
-        let mut css = css.split_off(first_quote_mark + 1);
-
-        // Replace :host selectors with the class name of the :host element
-        // A fake shadow-dom implementation.. if you will..
-        let css = css.replace(":host", &format!(".{}", class));
-
-        css_file.write(&css.into_bytes()).unwrap();
-        css_file.write("\n".as_bytes()).unwrap();
+/// format!("--_css_rs_5e1c2b0a-0: {}; ...", theme::PRIMARY, ...)
+///
+/// The expressions are spanned to where they are in the CSS so that a typo in one is a
+/// compile error that points there.
+fn style_value(
+    input: &CssInput,
+    class: &str,
+    interpolations: &[interpolation::Interpolation],
+    exprs: &[String],
+) -> proc_macro2::TokenStream {
+    let format_string = (0..exprs.len())
+        .map(|idx| format!("{}: {{}};", interpolation::custom_property(class, idx)))
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let args = exprs.iter().map(|expr| {
+        let offset = interpolations
+            .iter()
+            .find(|interpolation| interpolation.expr == *expr)
+            .map(|interpolation| interpolation.offset)
+            .unwrap_or(0);
+        let span = Span::from(input.span_at(offset, expr.len()));
+
+        expr.parse::<proc_macro2::TokenStream>()
+            .expect("Interpolations are Rust paths")
+            .into_iter()
+            .map(|mut token| {
+                token.set_span(span);
+                token
+            })
+            .collect::<proc_macro2::TokenStream>()
+    });
+
+    quote! { format!(#format_string, #(#args),*) }
+}
+
+/// A class name from a css! call. With `runtime = true` it comes with the CSS so that
+/// `css_rs` can inject it when it gets rendered.
+fn class_value(runtime: bool, name: &str, scope: &str, css: &str) -> proc_macro2::TokenStream {
+    if runtime {
+        quote! { ::css_rs::Css::new(#name, #scope, #css) }
+    } else {
+        quote! { #name }
+    }
+}
+
+fn class_type(runtime: bool) -> proc_macro2::TokenStream {
+    if runtime {
+        quote! { ::css_rs::Css }
+    } else {
+        quote! { &'static str }
+    }
+}
+
+/// Emit a warning for every property that browsers won't recognize
+#[cfg(feature = "warn-unknown-properties")]
+fn warn_unknown_properties(input: &CssInput, stylesheet: &parser::Stylesheet) {
+    for declaration in stylesheet.declarations() {
+        if properties::is_known_property(&declaration.property) {
+            continue;
+        }
+
+        let message = format!("unknown CSS property `{}`", declaration.property);
+        let warning = input
+            .span_at(declaration.offset, declaration.property.len())
+            .warning(message);
+
+        match properties::suggestion(&declaration.property) {
+            Some(suggestion) => warning.help(format!("did you mean `{}`?", suggestion)),
+            None => warning,
+        }
+        .emit();
+    }
+}
+
+/// The crate whose css! call we are expanding
+fn crate_name() -> String {
+    env::var("CARGO_CRATE_NAME")
+        .or_else(|_| env::var("CARGO_PKG_NAME"))
+        .unwrap_or_default()
+}
+
+/// The target whose css! call we are expanding. A package's library and binary can have the
+/// same crate name, so binaries get a suffix, such as `my_app.bin`.
+fn target_name() -> String {
+    match env::var("CARGO_BIN_NAME") {
+        Ok(_) => format!("{}.bin", crate_name()),
+        Err(_) => crate_name(),
+    }
+}
+
+/// The module whose css! call we are expanding, such as `views::nav_bar`, from the path of
+/// its file. Empty for the crate's root module.
+fn module_name() -> String {
+    let file = match call_site_file() {
+        Some(file) => file,
+        None => return String::new(),
+    };
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+
+    module_from_path(file.strip_prefix(&manifest_dir).unwrap_or(&file))
+}
+
+/// The file that the css! call that we are expanding is in
+fn call_site_file() -> Option<PathBuf> {
+    let file = proc_macro::Span::call_site().local_file()?;
+
+    // Paths are relative to the directory that rustc runs in, which is the workspace's
+    Some(env::current_dir().unwrap_or_default().join(file))
+}
+
+/// src/views/nav_bar.rs -> views::nav_bar
+fn module_from_path(path: &Path) -> String {
+    let mut segments = path
+        .with_extension("")
+        .iter()
+        .map(|segment| segment.to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+
+    if segments.first().map(|dir| dir == "src").unwrap_or(false) {
+        segments.remove(0);
+    }
+    let is_root = segments.len() == 1 && (segments[0] == "lib" || segments[0] == "main");
+    if is_root || segments.last().map(|file| file == "mod").unwrap_or(false) {
+        segments.pop();
+    }
+
+    segments.join("::")
+}
+
+/// "_css_rs_" followed by a hash of the crate name, module and CSS
+fn class_name(crate_name: &str, module: &str, css: &str) -> String {
+    let hash = fnv1a(format!("{}\0{}\0{}", crate_name, module, css).as_bytes());
+
+    format!("_css_rs_{:08x}", hash)
+}
+
+/// The 32 bit FNV-1a hash. Unlike the standard library's `DefaultHasher` its output is the
+/// same for every version of Rust.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+
+    for byte in bytes {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class_names_depend_on_crate_module_and_css() {
+        let class = class_name("app", "views", ":host { color: red; }");
+
+        assert_eq!(class, class_name("app", "views", ":host { color: red; }"));
+        assert_ne!(class, class_name("app", "views", ":host { color: blue; }"));
+        assert_ne!(class, class_name("app", "", ":host { color: red; }"));
+        assert_ne!(
+            class,
+            class_name("other_app", "views", ":host { color: red; }")
+        );
+
+        assert!(class.starts_with("_css_rs_"));
+        assert_eq!(class.len(), "_css_rs_".len() + 8);
+    }
+
+    #[test]
+    fn line_and_column() {
+        let css = ":host {\n  color: red;\n}";
+
+        assert_eq!(input::line_and_column(css, 0), (1, 1));
+        assert_eq!(
+            input::line_and_column(css, css.find("red").unwrap()),
+            (2, 10)
+        );
+    }
+
+    #[test]
+    fn modules_from_paths() {
+        assert_eq!(module_from_path(Path::new("src/lib.rs")), "");
+        assert_eq!(module_from_path(Path::new("src/views/mod.rs")), "views");
+        assert_eq!(
+            module_from_path(Path::new("src/views/main.rs")),
+            "views::main"
+        );
+        assert_eq!(
+            module_from_path(Path::new("src/views/nav_bar.rs")),
+            "views::nav_bar"
+        );
+    }
+
+    #[test]
+    fn fnv1a_test_vectors() {
+        assert_eq!(fnv1a(b""), 0x811c9dc5);
+        assert_eq!(fnv1a(b"a"), 0xe40c292c);
+        assert_eq!(fnv1a(b"foobar"), 0xbf9cf968);
     }
 }
@@ -0,0 +1,216 @@
+//! Turning nested rules into plain CSS.
+//!
+//! ```css
+//! :host {
+//!     color: red;
+//!
+//!     > div { display: flex; }
+//!     &:hover { color: blue; }
+//!
+//!     @media (max-width: 600px) {
+//!         display: none;
+//!     }
+//! }
+//! ```
+//!
+//! becomes
+//!
+//! ```css
+//! :host { color: red; }
+//! :host > div { display: flex; }
+//! :host:hover { color: blue; }
+//! @media (max-width: 600px) {
+//!     :host { display: none; }
+//! }
+//! ```
+
+use parser::{AtRule, Item, Rule};
+
+/// Whether an at-rule's block holds rules and declarations that are nested the same way as
+/// the block's parent rule, like `@media`. Other at-rules such as `@keyframes` are copied
+/// as they are.
+pub fn holds_rules(at_rule_name: &str) -> bool {
+    match at_rule_name {
+        "media" | "supports" | "document" | "container" | "layer" => true,
+        _ => false,
+    }
+}
+
+/// Flatten the items inside of the rule with the `parents` selectors, or the top level
+/// items if there is no parent rule. `offset` is where the parent rule is in the CSS.
+pub fn flatten(items: &[Item], parents: Option<&[String]>, offset: usize) -> Vec<Item> {
+    let mut declarations = vec![];
+    let mut flattened = vec![];
+
+    for item in items {
+        match item {
+            Item::Declaration(_) => declarations.push(item.clone()),
+            Item::Rule(rule) => {
+                let selectors = nest_selectors(parents, &rule.selector);
+                flattened.extend(flatten(&rule.items, Some(&selectors), rule.offset));
+            }
+            Item::AtRule(at_rule) => {
+                let block = match &at_rule.block {
+                    Some(block) if holds_rules(&at_rule.name) => {
+                        Some(flatten(block, parents, at_rule.offset))
+                    }
+                    block => block.clone(),
+                };
+
+                flattened.push(Item::AtRule(AtRule {
+                    name: at_rule.name.clone(),
+                    prelude: at_rule.prelude.clone(),
+                    offset: at_rule.offset,
+                    block,
+                }));
+            }
+        }
+    }
+
+    if declarations.is_empty() {
+        return flattened;
+    }
+
+    // The parent rule's own declarations come before any of its nested rules
+    let declarations = match parents {
+        Some(parents) => vec![Item::Rule(Rule {
+            selector: parents.join(", "),
+            offset,
+            items: declarations,
+        })],
+        // Such as the declarations in `@font-face`
+        None => declarations,
+    };
+
+    declarations.into_iter().chain(flattened).collect()
+}
+
+/// The full selectors of a rule that is nested inside of the `parents` selectors.
+///
+/// `&` is replaced with the parent selector, and selectors without a `&` are descendants of
+/// the parent, so "> div, &:hover" inside of ":host, .a" becomes
+/// [":host > div", ".a > div", ":host:hover", ".a:hover"].
+fn nest_selectors(parents: Option<&[String]>, selector: &str) -> Vec<String> {
+    let selectors = split_selectors(selector);
+
+    let parents = match parents {
+        Some(parents) => parents,
+        None => return selectors,
+    };
+
+    selectors
+        .iter()
+        .flat_map(|selector| {
+            parents.iter().map(move |parent| {
+                if selector.contains('&') {
+                    selector.replace('&', parent)
+                } else {
+                    format!("{} {}", parent, selector)
+                }
+            })
+        })
+        .collect()
+}
+
+/// "a, b:not(.c, .d)" -> ["a", "b:not(.c, .d)"]
+fn split_selectors(selector: &str) -> Vec<String> {
+    let mut selectors = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut quote = None;
+
+    for c in selector.chars() {
+        match (c, quote) {
+            (_, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('"', None) | ('\'', None) => quote = Some(c),
+            ('(', None) | ('[', None) => depth += 1,
+            (')', None) | (']', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                selectors.push(current.trim().to_string());
+                current = String::new();
+                continue;
+            }
+            _ => {}
+        }
+
+        current.push(c);
+    }
+    selectors.push(current.trim().to_string());
+
+    selectors
+        .into_iter()
+        .filter(|selector| !selector.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use parser;
+
+    #[test]
+    fn flatten_nested_rules() {
+        let css = r#"
+        :host {
+            color: red;
+
+            > div, &:hover {
+                display: flex;
+
+                span { color: blue; }
+            }
+
+            @media (max-width: 600px) {
+                display: none;
+
+                &.open { display: block; }
+            }
+
+            @keyframes fade {
+                from { opacity: 0; }
+            }
+        }
+        "#;
+
+        assert_eq!(
+            parser::parse(css).unwrap().to_css("app", &[]),
+            r#".app {
+    color: red;
+}
+.app > div, .app:hover {
+    display: flex;
+}
+.app > div span, .app:hover span {
+    color: blue;
+}
+@media (max-width: 600px) {
+    .app {
+        display: none;
+    }
+    .app.open {
+        display: block;
+    }
+}
+@keyframes app_fade {
+    from {
+        opacity: 0;
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn nest_selectors() {
+        let parents = vec![":host".to_string(), ".a".to_string()];
+
+        assert_eq!(
+            super::nest_selectors(Some(&parents), "> div, & + &"),
+            vec![":host > div", ".a > div", ":host + :host", ".a + .a"]
+        );
+        assert_eq!(
+            super::nest_selectors(None, r#"a:not(.b, .c), [title="d,e"]"#),
+            vec!["a:not(.b, .c)", r#"[title="d,e"]"#]
+        );
+    }
+}
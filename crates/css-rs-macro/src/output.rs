@@ -0,0 +1,422 @@
+//! Writing the CSS from every `css!` call to the configured stylesheets.
+//!
+//! Each target's CSS goes into its own fragment file next to the output file, and the
+//! stylesheets are then recreated from all of the fragments. So compiling one crate never
+//! loses the CSS of the others, no matter which crates cargo decides to recompile.
+
+use config::{Config, Split};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+
+lazy_static! {
+    /// The CSS of every css! call in each target that this compiler process has expanded, by
+    /// target and then by class.
+    ///
+    /// A process doesn't always expand every css! call in a target, such as when an editor
+    /// only expands the file that you have open, so a target's CSS starts out as whatever is
+    /// already in its fragment and then only ever gets added to. CSS from a call that was
+    /// changed or deleted stays in the fragment since nothing tells us that the call is gone.
+    static ref TARGET_CSS: Mutex<HashMap<String, BTreeMap<String, Block>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// The CSS from one css! call
+#[derive(Debug, Clone, PartialEq)]
+struct Block {
+    crate_name: String,
+    /// The module that the css! call is in, such as `views::nav_bar`. Empty for the crate's
+    /// root module.
+    module: String,
+    class: String,
+    /// The top level rules and at-rules from `Stylesheet::rules`
+    rules: Vec<String>,
+}
+
+impl Block {
+    /// `my_crate::views::nav_bar`
+    fn module_path(&self) -> String {
+        if self.module.is_empty() {
+            self.crate_name.clone()
+        } else {
+            format!("{}::{}", self.crate_name, self.module)
+        }
+    }
+}
+
+/// Save a class's CSS to its target's fragment, then recreate the stylesheets. Expanding a
+/// css! call whose CSS is already saved does nothing.
+pub fn write_css(
+    config: &Config,
+    output_css: &Path,
+    target: &str,
+    crate_name: &str,
+    module: &str,
+    class: &str,
+    rules: &[String],
+) {
+    let fragments_dir = fragments_dir(output_css);
+    let fragment = fragments_dir.join(format!("{}.css", target));
+
+    let mut target_css = TARGET_CSS.lock().unwrap();
+    let target_css = target_css.entry(target.to_string()).or_insert_with(|| {
+        read_fragment(&fragment)
+            .into_iter()
+            .map(|block| (block.class.clone(), block))
+            .collect()
+    });
+
+    let block = Block {
+        crate_name: crate_name.to_string(),
+        module: module.to_string(),
+        class: class.to_string(),
+        rules: rules.to_vec(),
+    };
+    // The same class has the same rules unless the output's `minify` setting changed
+    if target_css.get(class) == Some(&block) {
+        return;
+    }
+    target_css.insert(class.to_string(), block);
+
+    fs::create_dir_all(&fragments_dir).unwrap();
+    write_atomically(
+        &fragment,
+        &target_css.values().map(format_block).collect::<String>(),
+    );
+
+    let blocks = read_fragments(&fragments_dir);
+    for (path, contents) in stylesheets(config, output_css, &blocks) {
+        write_atomically(&path, &contents);
+    }
+}
+
+/// /path/to/app.css -> /path/to/app.css.fragments
+fn fragments_dir(output_css: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.fragments", output_css.display()))
+}
+
+/// A fragment is every block in a target, each one a `/* crate class module */` header and
+/// then a line for each rule. The parser removes comments from the CSS so a header can't be
+/// mistaken for a rule.
+fn format_block(block: &Block) -> String {
+    let mut formatted = format!(
+        "/* {} {} {} */\n",
+        block.crate_name, block.class, block.module
+    );
+    for rule in block.rules.iter() {
+        formatted.push_str(&escape_rule(rule));
+        formatted.push('\n');
+    }
+    formatted
+}
+
+/// Pretty rules span several lines, so their newlines get escaped like in a Rust string
+fn escape_rule(rule: &str) -> String {
+    rule.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_rule(line: &str) -> String {
+    let mut rule = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            rule.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => rule.push('\n'),
+            Some(escaped) => rule.push(escaped),
+            None => {}
+        }
+    }
+
+    rule
+}
+
+/// All of the blocks in all of the fragments, sorted by crate, module and class so that the
+/// output doesn't depend on the order that things were compiled in.
+fn read_fragments(fragments_dir: &Path) -> Vec<Block> {
+    let mut blocks: Vec<Block> = fs::read_dir(fragments_dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|ext| ext == "css").unwrap_or(false))
+        .flat_map(|fragment| read_fragment(&fragment))
+        .collect();
+
+    blocks.sort_by(|a, b| {
+        (&a.crate_name, &a.module, &a.class).cmp(&(&b.crate_name, &b.module, &b.class))
+    });
+    blocks
+}
+
+/// The blocks in a fragment, or none if it hasn't been written yet
+fn read_fragment(fragment: &Path) -> Vec<Block> {
+    let mut blocks = vec![];
+
+    let contents = match fs::read_to_string(fragment) {
+        Ok(contents) => contents,
+        Err(_) => return blocks,
+    };
+
+    for line in contents.lines() {
+        if line.starts_with("/* ") && line.ends_with(" */") {
+            let mut header = line[3..line.len() - 3].split(' ');
+
+            blocks.push(Block {
+                crate_name: header.next().unwrap_or("").to_string(),
+                class: header.next().unwrap_or("").to_string(),
+                module: header.next().unwrap_or("").to_string(),
+                rules: vec![],
+            });
+        } else if let Some(block) = blocks.last_mut() {
+            block.rules.push(unescape_rule(line));
+        }
+    }
+
+    blocks
+}
+
+/// The contents of every file that we write: the output file, the split files and the
+/// manifest
+fn stylesheets(config: &Config, output_css: &Path, blocks: &[Block]) -> Vec<(PathBuf, String)> {
+    let mut files: BTreeMap<PathBuf, Vec<&Block>> = BTreeMap::new();
+    files.insert(output_css.to_path_buf(), blocks.iter().collect());
+
+    // The file that each module's CSS is in
+    let mut manifest: BTreeMap<String, (PathBuf, Vec<&str>)> = BTreeMap::new();
+
+    for block in blocks {
+        let file = match config.split {
+            Split::None => output_css.to_path_buf(),
+            Split::Crate => split_path(output_css, &block.crate_name),
+            Split::Module => split_path(output_css, &block.module_path().replace("::", ".")),
+        };
+
+        if config.split != Split::None {
+            files
+                .entry(file.clone())
+                .or_insert_with(Vec::new)
+                .push(block);
+        }
+
+        // css! calls with the same CSS in the same module share a class
+        let classes = &mut manifest
+            .entry(block.module_path())
+            .or_insert_with(|| (file, vec![]))
+            .1;
+        if !classes.contains(&block.class.as_str()) {
+            classes.push(&block.class);
+        }
+    }
+
+    let mut contents: Vec<(PathBuf, String)> = files
+        .into_iter()
+        .map(|(path, blocks)| (path, combine(&blocks)))
+        .collect();
+
+    if config.manifest {
+        contents.push((
+            split_path(output_css, "manifest").with_extension("json"),
+            manifest_json(output_css, &manifest),
+        ));
+    }
+
+    contents
+}
+
+/// /path/to/app.css -> /path/to/app.my_crate.css
+fn split_path(output_css: &Path, name: &str) -> PathBuf {
+    let stem = output_css
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    output_css.with_file_name(format!("{}.{}.css", stem, name))
+}
+
+/// The CSS from these blocks, without any rules that are exactly the same as an earlier
+/// rule. Those come from global selectors, `@import`s and `@font-face`s that show up in
+/// several css! calls.
+fn combine(blocks: &[&Block]) -> String {
+    let mut rules: Vec<&str> = vec![];
+
+    for block in blocks {
+        for rule in block.rules.iter() {
+            if !rules.contains(&rule.as_str()) {
+                rules.push(rule);
+            }
+        }
+    }
+
+    rules.concat()
+}
+
+/// { "my_crate::views::nav_bar": { "file": "app.css", "classes": ["_css_rs_5e1c2b0a"] } }
+fn manifest_json(output_css: &Path, manifest: &BTreeMap<String, (PathBuf, Vec<&str>)>) -> String {
+    let modules = manifest
+        .iter()
+        .map(|(module, (file, classes))| {
+            // Relative to the manifest, which is next to the output file
+            let file = file
+                .strip_prefix(output_css.parent().unwrap_or(Path::new("")))
+                .unwrap_or(file);
+            let classes = classes
+                .iter()
+                .map(|class| format!(r#""{}""#, class))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            format!(
+                r#"  "{}": {{ "file": "{}", "classes": [{}] }}"#,
+                module,
+                file.display(),
+                classes
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",\n");
+
+    format!("{{\n{}\n}}\n", modules)
+}
+
+/// Crates get compiled in parallel, so we write to a temporary file and then rename it in
+/// order to never leave a half written file for another crate's compiler to read.
+fn write_atomically(path: &Path, contents: &str) {
+    let tmp = PathBuf::from(format!("{}.{}.tmp", path.display(), process::id()));
+
+    fs::write(&tmp, contents).unwrap();
+    fs::rename(&tmp, path).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn block(crate_name: &str, module: &str, class: &str, rules: &[&str]) -> Block {
+        Block {
+            crate_name: crate_name.to_string(),
+            module: module.to_string(),
+            class: class.to_string(),
+            rules: rules.iter().map(|rule| rule.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn fragments_keep_multi_line_rules() {
+        let block = block(
+            "app",
+            "views::nav",
+            "_n",
+            &[
+                "._n {\n    transition: opacity 0.3s,\ntransform 0.3s;\n}\n",
+                "._n::before {\n    content: \"\\201C\";\n}\n",
+            ],
+        );
+
+        let fragments_dir = env::temp_dir().join(format!("css-rs-fragments-{}", process::id()));
+        fs::create_dir_all(&fragments_dir).unwrap();
+        fs::write(fragments_dir.join("app.css"), format_block(&block)).unwrap();
+
+        assert_eq!(read_fragments(&fragments_dir), vec![block]);
+
+        fs::remove_dir_all(&fragments_dir).unwrap();
+    }
+
+    #[test]
+    fn split_dedupe_and_manifest() {
+        let blocks = vec![
+            block("app", "", "_b", &["._b{color:red}"]),
+            // Another css! call with the same CSS in the same module
+            block("app", "", "_b", &["._b{color:red}"]),
+            block(
+                "app",
+                "views::nav",
+                "_n",
+                &["._n{color:blue}", ".red{color:red}"],
+            ),
+            block("other", "", "_o", &[".red{color:red}"]),
+        ];
+        let config = Config {
+            output: None,
+            minify: true,
+            split: Split::Module,
+            manifest: true,
+            runtime: false,
+        };
+
+        let files = stylesheets(&config, Path::new("/build/app.css"), &blocks);
+        let file = |path: &str| {
+            files
+                .iter()
+                .find(|(file, _)| file == Path::new(path))
+                .map(|(_, contents)| contents.as_str())
+        };
+
+        assert_eq!(files.len(), 5);
+        assert_eq!(
+            file("/build/app.css"),
+            Some("._b{color:red}._n{color:blue}.red{color:red}")
+        );
+        assert_eq!(file("/build/app.app.css"), Some("._b{color:red}"));
+        assert_eq!(
+            file("/build/app.app.views.nav.css"),
+            Some("._n{color:blue}.red{color:red}")
+        );
+        assert_eq!(file("/build/app.other.css"), Some(".red{color:red}"));
+        assert_eq!(
+            file("/build/app.manifest.json"),
+            Some(
+                r#"{
+  "app": { "file": "app.app.css", "classes": ["_b"] },
+  "app::views::nav": { "file": "app.app.views.nav.css", "classes": ["_n"] },
+  "other": { "file": "app.other.css", "classes": ["_o"] }
+}
+"#
+            )
+        );
+    }
+
+    #[test]
+    fn expanding_only_adds_to_a_targets_fragment() {
+        let output_css = env::temp_dir().join(format!("css-rs-targets-{}.css", process::id()));
+        let config = Config {
+            output: None,
+            minify: true,
+            split: Split::None,
+            manifest: false,
+            runtime: false,
+        };
+        let write = |target: &str, class: &str, rule: &str| {
+            let rules = vec![rule.to_string()];
+            write_css(&config, &output_css, target, "app", "", class, &rules)
+        };
+
+        // Saved by an earlier process that expanded every css! call in the library
+        let fragments_dir = fragments_dir(&output_css);
+        fs::create_dir_all(&fragments_dir).unwrap();
+        fs::write(
+            fragments_dir.join("app.css"),
+            format_block(&block("app", "", "_a", &["._a{color:red}"])),
+        )
+        .unwrap();
+
+        // An expansion of one of the library's calls keeps the others
+        write("app", "_b", "._b{color:blue}");
+        // The package's binary has the same crate name as its library
+        write("app.bin", "_c", "._c{color:green}");
+        write("app.bin", "_c", "._c {\n    color: green;\n}\n");
+
+        assert_eq!(
+            fs::read_to_string(&output_css).unwrap(),
+            "._a{color:red}._b{color:blue}._c {\n    color: green;\n}\n"
+        );
+
+        fs::remove_file(&output_css).unwrap();
+        fs::remove_dir_all(fragments_dir).unwrap();
+    }
+}
@@ -0,0 +1,650 @@
+//! Parsing the CSS inside of a `css!` call so that mistakes are caught at compile time.
+//!
+//! We only need to understand CSS's structure (rules, at-rules and declarations), so
+//! selectors, at-rule preludes and values are kept as text.
+
+use nesting;
+use scope::Scope;
+
+/// A parsed `css!` call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stylesheet {
+    pub items: Vec<Item>,
+    /// Every class is local to the stylesheet, like in CSS modules, instead of only `:host`
+    pub module: bool,
+}
+
+/// Something inside of a stylesheet or block
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Rule(Rule),
+    AtRule(AtRule),
+    Declaration(Declaration),
+}
+
+/// `:host > div { display: flex; }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub selector: String,
+    /// Where the selector starts in the CSS
+    pub offset: usize,
+    pub items: Vec<Item>,
+}
+
+/// `@media (max-width: 600px) { ... }` or `@import "other.css";`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtRule {
+    /// `media`, without the `@`
+    pub name: String,
+    /// `(max-width: 600px)`
+    pub prelude: String,
+    /// Where the `@` is in the CSS
+    pub offset: usize,
+    /// `None` for at-rules such as `@import` that end with a `;`
+    pub block: Option<Vec<Item>>,
+}
+
+/// `color: red`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub property: String,
+    pub value: String,
+    /// Where the property starts in the CSS
+    pub offset: usize,
+}
+
+/// Why the CSS could not be parsed
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    /// Where in the CSS the problem is
+    pub offset: usize,
+}
+
+/// Parse the CSS from a `css!` call.
+pub fn parse(css: &str) -> Result<Stylesheet, ParseError> {
+    let mut parser = Parser { css, pos: 0 };
+
+    let items = parser.parse_items(None)?;
+    validate(&items, false)?;
+
+    Ok(Stylesheet {
+        items,
+        module: false,
+    })
+}
+
+struct Parser<'a> {
+    css: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Parse items until the end of the block that was opened by the `{` at `block_start`,
+    /// or until the end of the CSS for the top level.
+    fn parse_items(&mut self, block_start: Option<usize>) -> Result<Vec<Item>, ParseError> {
+        let mut items = vec![];
+
+        loop {
+            self.skip_whitespace_and_comments()?;
+
+            let start = self.pos;
+
+            match self.peek() {
+                None => {
+                    return match block_start {
+                        Some(block_start) => Err(error(block_start, "this `{` is never closed")),
+                        None => Ok(items),
+                    };
+                }
+                Some('}') => {
+                    return match block_start {
+                        Some(_) => {
+                            self.pos += 1;
+                            Ok(items)
+                        }
+                        None => Err(error(start, "unexpected `}` without a matching `{`")),
+                    };
+                }
+                Some(';') => self.pos += 1,
+                Some('@') => items.push(Item::AtRule(self.parse_at_rule()?)),
+                Some(_) => {
+                    let (text, end) = self.read_until_block_or_end()?;
+
+                    match end {
+                        Some('{') => {
+                            let selector = text.trim().to_string();
+                            if selector.is_empty() {
+                                return Err(error(start, "missing a selector before `{`"));
+                            }
+
+                            let block_start = self.pos;
+                            self.pos += 1;
+
+                            items.push(Item::Rule(Rule {
+                                selector,
+                                offset: start,
+                                items: self.parse_items(Some(block_start))?,
+                            }));
+                        }
+                        _ => {
+                            if end == Some(';') {
+                                self.pos += 1;
+                            }
+
+                            items.push(Item::Declaration(parse_declaration(&text, start)?));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// `@media (max-width: 600px) { ... }`
+    fn parse_at_rule(&mut self) -> Result<AtRule, ParseError> {
+        let offset = self.pos;
+        self.pos += 1;
+
+        let name_len = self.css[self.pos..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(self.css.len() - self.pos);
+        let name = self.css[self.pos..self.pos + name_len].to_string();
+        if name.is_empty() {
+            return Err(error(offset, "missing an at-rule name after `@`"));
+        }
+        self.pos += name_len;
+
+        let (prelude, end) = self.read_until_block_or_end()?;
+        let prelude = prelude.trim().to_string();
+
+        let block = match end {
+            Some('{') => {
+                let block_start = self.pos;
+                self.pos += 1;
+                Some(self.parse_items(Some(block_start))?)
+            }
+            Some(';') => {
+                self.pos += 1;
+                None
+            }
+            _ => None,
+        };
+
+        Ok(AtRule {
+            name,
+            prelude,
+            offset,
+            block,
+        })
+    }
+
+    /// Read until a `{`, `;` or `}` that isn't inside of a string or parentheses, leaving
+    /// `pos` on it. Comments are left out of the text that we return.
+    fn read_until_block_or_end(&mut self) -> Result<(String, Option<char>), ParseError> {
+        let mut text = String::new();
+        let mut open_parens = vec![];
+
+        while let Some(c) = self.peek() {
+            match c {
+                '{' | ';' | '}' if open_parens.is_empty() => return Ok((text, Some(c))),
+                '"' | '\'' => {
+                    let string_start = self.pos;
+                    text.push_str(self.read_string(c, string_start)?);
+                    continue;
+                }
+                '/' if self.css[self.pos..].starts_with("/*") => {
+                    self.skip_comment()?;
+                    text.push(' ');
+                    continue;
+                }
+                '(' => open_parens.push(self.pos),
+                ')' => {
+                    if open_parens.pop().is_none() {
+                        return Err(error(self.pos, "unexpected `)` without a matching `(`"));
+                    }
+                }
+                _ => {}
+            }
+
+            text.push(c);
+            self.pos += c.len_utf8();
+        }
+
+        match open_parens.pop() {
+            Some(open_paren) => Err(error(open_paren, "this `(` is never closed")),
+            None => Ok((text, None)),
+        }
+    }
+
+    /// A quoted string, including its quotes
+    fn read_string(&mut self, quote: char, start: usize) -> Result<&'a str, ParseError> {
+        let mut escaped = false;
+
+        for (idx, c) in self.css[start + 1..].char_indices() {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '\n' if !escaped => break,
+                c if c == quote && !escaped => {
+                    self.pos = start + 1 + idx + 1;
+                    return Ok(&self.css[start..self.pos]);
+                }
+                _ => escaped = false,
+            }
+        }
+
+        Err(error(start, "this string is never closed"))
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => self.pos += c.len_utf8(),
+                Some('/') if self.css[self.pos..].starts_with("/*") => self.skip_comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), ParseError> {
+        match self.css[self.pos + 2..].find("*/") {
+            Some(end) => {
+                self.pos += 2 + end + 2;
+                Ok(())
+            }
+            None => Err(error(self.pos, "this comment is never closed")),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.css[self.pos..].chars().next()
+    }
+}
+
+/// `color: red` -> Declaration { property: "color", value: "red" }
+fn parse_declaration(text: &str, offset: usize) -> Result<Declaration, ParseError> {
+    let text = text.trim();
+
+    let colon = match text.find(':') {
+        Some(colon) => colon,
+        None => {
+            return Err(error(
+                offset,
+                &format!("expected a `:` after `{}`, i.e. `color: red;`", text),
+            ));
+        }
+    };
+
+    let property = text[..colon].trim();
+    let value = text[colon + 1..].trim();
+
+    if property.is_empty() {
+        return Err(error(offset, "missing a property name before `:`"));
+    }
+    if !property
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(error(
+            offset,
+            &format!("`{}` is not a valid property name", property),
+        ));
+    }
+    if value.is_empty() {
+        return Err(error(
+            offset,
+            &format!("missing a value for `{}`", property),
+        ));
+    }
+
+    Ok(Declaration {
+        property: property.to_string(),
+        value: value.to_string(),
+        offset,
+    })
+}
+
+/// Declarations need to be in a rule, and `&` needs a parent rule to refer to.
+fn validate(items: &[Item], in_rule: bool) -> Result<(), ParseError> {
+    for item in items {
+        match item {
+            Item::Declaration(declaration) if !in_rule => {
+                return Err(error(
+                    declaration.offset,
+                    &format!(
+                        "`{}` needs to be inside of a rule, i.e. `:host {{ {}: {}; }}`",
+                        declaration.property, declaration.property, declaration.value
+                    ),
+                ));
+            }
+            Item::Rule(rule) if !in_rule && rule.selector.contains('&') => {
+                return Err(error(
+                    rule.offset,
+                    "`&` refers to the parent rule, so it can only be used in a nested rule",
+                ));
+            }
+            Item::Rule(rule) => validate(&rule.items, true)?,
+            Item::AtRule(AtRule {
+                name,
+                block: Some(items),
+                ..
+            }) if nesting::holds_rules(name) => validate(items, in_rule)?,
+            // `@font-face`, `@keyframes` and friends have their own rules about what goes
+            // inside of them
+            Item::AtRule(_) | Item::Declaration(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn error(offset: usize, message: &str) -> ParseError {
+    ParseError {
+        message: message.to_string(),
+        offset,
+    }
+}
+
+impl Stylesheet {
+    /// All of the rules as pretty CSS
+    #[cfg(test)]
+    pub fn to_css(&self, class: &str, interpolations: &[String]) -> String {
+        self.rules(class, interpolations, false).concat()
+    }
+
+    /// Write the stylesheet back out as plain CSS without any nesting, scoped to the class,
+    /// with a string for each top level rule or at-rule. `interpolations` are the Rust
+    /// expressions whose values are in custom properties. Minified rules leave out all of the
+    /// whitespace that browsers don't need.
+    pub fn rules(&self, class: &str, interpolations: &[String], minify: bool) -> Vec<String> {
+        let scope = Scope::new(class, self, interpolations);
+        let depth = if minify { None } else { Some(0) };
+
+        nesting::flatten(&self.items, None, 0)
+            .iter()
+            .map(|item| {
+                let mut css = String::new();
+                write_items(&mut css, ::std::slice::from_ref(item), &scope, depth);
+                css
+            })
+            .collect()
+    }
+
+    /// Every declaration in the stylesheet, including those in at-rules
+    #[cfg(any(test, feature = "warn-unknown-properties"))]
+    pub fn declarations(&self) -> Vec<&Declaration> {
+        let mut declarations = vec![];
+        collect_declarations(&self.items, &mut declarations);
+        declarations
+    }
+}
+
+/// Pretty CSS puts every selector and declaration on its own line, indented by the depth.
+/// Without a depth the CSS is minified.
+fn write_items(css: &mut String, items: &[Item], scope: &Scope, depth: Option<usize>) {
+    let indent = depth.map(|depth| "    ".repeat(depth)).unwrap_or_default();
+    let newline = if depth.is_some() { "\n" } else { "" };
+    let inner = depth.map(|depth| depth + 1);
+
+    for item in items {
+        match item {
+            Item::Rule(rule) => {
+                let selector = scope.selector(&rule.selector);
+                match depth {
+                    Some(_) => css.push_str(&format!("{}{} {{\n", indent, selector)),
+                    None => css.push_str(&format!("{}{{", minify_text(&selector, ",>+~"))),
+                }
+                write_items(css, &rule.items, scope, inner);
+                close_block(css, &indent, newline);
+            }
+            Item::AtRule(at_rule) => {
+                css.push_str(&format!("{}@{}", indent, at_rule.name));
+                if !at_rule.prelude.is_empty() {
+                    let prelude = scope.at_rule_prelude(&at_rule.name, &at_rule.prelude);
+                    match depth {
+                        Some(_) => css.push_str(&format!(" {}", prelude)),
+                        None => css.push_str(&format!(" {}", minify_text(&prelude, ","))),
+                    }
+                }
+
+                match &at_rule.block {
+                    Some(items) => {
+                        css.push_str(if depth.is_some() { " {\n" } else { "{" });
+                        write_items(css, items, scope, inner);
+                        close_block(css, &indent, newline);
+                    }
+                    None => css.push_str(&format!(";{}", newline)),
+                }
+            }
+            Item::Declaration(declaration) => {
+                let value = scope.value(&declaration.property, &declaration.value);
+                match depth {
+                    Some(_) => {
+                        css.push_str(&format!("{}{}: {};\n", indent, declaration.property, value))
+                    }
+                    None => css.push_str(&format!(
+                        "{}:{};",
+                        declaration.property,
+                        minify_text(&value, ",")
+                    )),
+                }
+            }
+        }
+    }
+}
+
+fn close_block(css: &mut String, indent: &str, newline: &str) {
+    // When minified the last declaration in a block doesn't need a semicolon
+    if newline.is_empty() && css.ends_with(';') {
+        css.pop();
+    }
+    css.push_str(&format!("{}}}{}", indent, newline));
+}
+
+/// Collapse whitespace to a single space, leaving out whitespace next to the `punctuation`
+/// and inside of strings alone.
+fn minify_text(text: &str, punctuation: &str) -> String {
+    let mut minified = String::new();
+    let mut quote = None;
+    let mut pending_space = false;
+
+    for c in text.trim().chars() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                minified.push(c);
+                continue;
+            }
+            None if c.is_whitespace() => {
+                pending_space = true;
+                continue;
+            }
+            None => {}
+        }
+
+        let next_to_punctuation = punctuation.contains(c)
+            || minified
+                .chars()
+                .last()
+                .map(|last| punctuation.contains(last))
+                .unwrap_or(true);
+        if pending_space && !next_to_punctuation {
+            minified.push(' ');
+        }
+        pending_space = false;
+
+        if c == '"' || c == '\'' {
+            quote = Some(c);
+        }
+        minified.push(c);
+    }
+
+    minified
+}
+
+#[cfg(any(test, feature = "warn-unknown-properties"))]
+fn collect_declarations<'a>(items: &'a [Item], declarations: &mut Vec<&'a Declaration>) {
+    for item in items {
+        match item {
+            Item::Rule(rule) => collect_declarations(&rule.items, declarations),
+            Item::AtRule(AtRule {
+                block: Some(items), ..
+            }) => collect_declarations(items, declarations),
+            Item::AtRule(_) => {}
+            Item::Declaration(declaration) => declarations.push(declaration),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rules_and_at_rules() {
+        let css = r#"
+        /* The host element */
+        :host > div, :host:hover {
+            color: red;
+            background: url("a;b{c}.png") no-repeat
+        }
+
+        @import "other.css";
+
+        @media (max-width: 600px) {
+            :host { display: none !important; }
+        }
+        "#;
+
+        let stylesheet = parse(css).unwrap();
+
+        assert_eq!(
+            stylesheet.to_css("app", &[]),
+            r#".app > div, .app:hover {
+    color: red;
+    background: url("a;b{c}.png") no-repeat;
+}
+@import "other.css";
+@media (max-width: 600px) {
+    .app {
+        display: none !important;
+    }
+}
+"#
+        );
+
+        let properties = stylesheet
+            .declarations()
+            .iter()
+            .map(|declaration| declaration.property.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(properties, vec!["color", "background", "display"]);
+    }
+
+    #[test]
+    fn minified_rules() {
+        let css = r#"
+        :host > div, :host:hover {
+            color: red;
+            font-family: "Avenir  Next", sans-serif;
+        }
+        @import url("x.css");
+        @media (max-width: 600px) {
+            :host { margin: 0 auto; }
+        }
+        "#;
+
+        assert_eq!(
+            parse(css).unwrap().rules("app", &[], true),
+            vec![
+                r#".app>div,.app:hover{color:red;font-family:"Avenir  Next",sans-serif}"#,
+                r#"@import url("x.css");"#,
+                "@media (max-width: 600px){.app{margin:0 auto}}",
+            ]
+        );
+    }
+
+    #[test]
+    fn minified_multi_line_values() {
+        let css = r#"
+:host {
+    transition: opacity 0.3s,
+transform 0.3s;
+    grid-template-areas: "a a"
+                         "b b";
+}
+"#;
+
+        assert_eq!(
+            parse(css).unwrap().rules("app", &[], true),
+            vec![r#".app{transition:opacity 0.3s,transform 0.3s;grid-template-areas:"a a" "b b"}"#]
+        );
+    }
+
+    #[test]
+    fn offsets() {
+        let css = ":host {\n  color: red;\n}";
+        let stylesheet = parse(css).unwrap();
+
+        assert_eq!(
+            stylesheet.declarations()[0].offset,
+            css.find("color").unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error_at = |css: &str| {
+            let error = parse(css).unwrap_err();
+            (error.offset, error.message)
+        };
+
+        assert_eq!(
+            error_at(":host { color: red; "),
+            (6, "this `{` is never closed".to_string())
+        );
+        assert_eq!(
+            error_at(":host { color: red; } }"),
+            (22, "unexpected `}` without a matching `{`".to_string())
+        );
+        assert_eq!(
+            error_at(":host { color red; }"),
+            (
+                8,
+                "expected a `:` after `color red`, i.e. `color: red;`".to_string()
+            )
+        );
+        assert_eq!(
+            error_at(":host { color: ; }"),
+            (8, "missing a value for `color`".to_string())
+        );
+        assert_eq!(
+            error_at("color: red;"),
+            (
+                0,
+                "`color` needs to be inside of a rule, i.e. `:host { color: red; }`".to_string()
+            )
+        );
+        assert_eq!(
+            error_at("&:hover { color: red; }"),
+            (
+                0,
+                "`&` refers to the parent rule, so it can only be used in a nested rule"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            error_at(":host { content: \"oops; }"),
+            (17, "this string is never closed".to_string())
+        );
+        assert_eq!(
+            error_at(":host { width: calc(1px + 2px; }"),
+            (19, "this `(` is never closed".to_string())
+        );
+        assert_eq!(
+            error_at(":host { /* color: red; }"),
+            (8, "this comment is never closed".to_string())
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! Scoping a `css!` call's CSS to its generated class so that it can't clash with the CSS
+//! from other `css!` calls.
+//!
+//! `:host` becomes the generated class. In a `module` stylesheet every class selector is
+//! local instead, like in CSS modules, so `.item` becomes `._css_rs_5e1c2b0a_item`. Names of
+//! `@keyframes` are always local.
+
+use interpolation;
+use parser::{AtRule, Item, ParseError, Stylesheet};
+
+/// How to rename the selectors and names in a stylesheet
+pub struct Scope {
+    /// The generated class, such as `_css_rs_5e1c2b0a`
+    class: String,
+    /// Whether `.item` is local to this stylesheet
+    local_classes: bool,
+    /// The `@keyframes` defined in this stylesheet
+    keyframes: Vec<String>,
+    /// The Rust expressions that are interpolated into values
+    interpolations: Vec<String>,
+}
+
+impl Scope {
+    pub fn new(class: &str, stylesheet: &Stylesheet, interpolations: &[String]) -> Scope {
+        let mut keyframes = vec![];
+        collect_keyframes(&stylesheet.items, &mut keyframes);
+
+        Scope {
+            class: class.to_string(),
+            local_classes: stylesheet.module,
+            keyframes,
+            interpolations: interpolations.to_vec(),
+        }
+    }
+
+    /// `.item` -> `_css_rs_5e1c2b0a_item`
+    pub fn local_name(&self, name: &str) -> String {
+        format!("{}_{}", self.class, name)
+    }
+
+    /// `:host > .item` -> `._css_rs_5e1c2b0a > .item`, or
+    /// `.list > .item` -> `._css_rs_5e1c2b0a_list > ._css_rs_5e1c2b0a_item` when classes are
+    /// local
+    pub fn selector(&self, selector: &str) -> String {
+        let selector = selector.replace(":host", &format!(".{}", self.class));
+
+        if self.local_classes {
+            map_class_names(selector.as_str(), |name, _| self.local_name(name))
+        } else {
+            selector
+        }
+    }
+
+    /// `fade` in `@keyframes fade`
+    pub fn at_rule_prelude(&self, name: &str, prelude: &str) -> String {
+        if is_keyframes(name) {
+            self.local_name(prelude)
+        } else {
+            prelude.to_string()
+        }
+    }
+
+    /// `fade` in `animation: fade 1s`, if `fade` is one of our `@keyframes`, and
+    /// interpolations such as `{theme::PRIMARY}`
+    pub fn value(&self, property: &str, value: &str) -> String {
+        let value =
+            &interpolation::replace_interpolations(value, &self.class, &self.interpolations);
+
+        let property = property.to_lowercase();
+        let is_animation = property.ends_with("animation") || property.ends_with("animation-name");
+
+        if !is_animation || self.keyframes.is_empty() {
+            return value.to_string();
+        }
+
+        map_words(value, |word| {
+            if self.keyframes.iter().any(|name| name == word) {
+                self.local_name(word)
+            } else {
+                word.to_string()
+            }
+        })
+    }
+}
+
+/// The local classes that a `css!` call should return, in the order that they first appear
+/// and with the offset of where that is. Empty unless the stylesheet is a `module`.
+pub fn local_classes(stylesheet: &Stylesheet) -> Vec<(String, usize)> {
+    let mut classes = vec![];
+
+    if stylesheet.module {
+        collect_classes(&stylesheet.items, &mut classes);
+    }
+
+    classes
+}
+
+/// `some-class` -> `some_class`
+pub fn field_name(class: &str) -> String {
+    class.replace('-', "_")
+}
+
+/// A `module` stylesheet returns a struct with a field for every class, so each class needs
+/// to be a different Rust identifier. `interpolations` means that the struct also has a
+/// `style` field.
+pub fn check_module(stylesheet: &Stylesheet, interpolations: bool) -> Result<(), ParseError> {
+    if !stylesheet.module {
+        return Ok(());
+    }
+
+    if let Some(offset) = find_host(&stylesheet.items) {
+        return Err(ParseError {
+            message: "`:host` can't be used in a `module` css! call, where every class is \
+                      local. Style one of its classes instead."
+                .to_string(),
+            offset,
+        });
+    }
+
+    let classes = local_classes(stylesheet);
+    if classes.is_empty() {
+        return Err(ParseError {
+            message: "a `module` css! call returns a field for each of its classes, but there \
+                      aren't any"
+                .to_string(),
+            offset: 0,
+        });
+    }
+
+    for (idx, (class, offset)) in classes.iter().enumerate() {
+        let field = field_name(class);
+        let error = |message: String| {
+            Err(ParseError {
+                message,
+                offset: *offset,
+            })
+        };
+
+        if field == "_" || RUST_KEYWORDS.contains(&field.as_str()) {
+            return error(format!(
+                "`.{}` can't be a field of the struct that css! returns since `{}` is a Rust \
+                 keyword",
+                class, field
+            ));
+        }
+
+        if interpolations && field == "style" {
+            return error(
+                "`.style` can't be a local class when there are Rust values in the CSS, since \
+                 they are set through a `style` field"
+                    .to_string(),
+            );
+        }
+
+        if let Some((other, _)) = classes[..idx]
+            .iter()
+            .find(|(other, _)| field_name(other) == field)
+        {
+            return error(format!(
+                "`.{}` and `.{}` would both be the `{}` field of the struct that css! returns",
+                other, class, field
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Where the first `:host` is
+fn find_host(items: &[Item]) -> Option<usize> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Rule(rule) => match rule.selector.find(":host") {
+                Some(idx) => Some(rule.offset + idx),
+                None => find_host(&rule.items),
+            },
+            Item::AtRule(AtRule {
+                block: Some(items), ..
+            }) => find_host(items),
+            _ => None,
+        })
+        .next()
+}
+
+fn collect_classes(items: &[Item], classes: &mut Vec<(String, usize)>) {
+    for item in items {
+        match item {
+            Item::Rule(rule) => {
+                map_class_names(&rule.selector, |name, idx| {
+                    if !classes.iter().any(|(class, _)| class == name) {
+                        classes.push((name.to_string(), rule.offset + idx));
+                    }
+                    name.to_string()
+                });
+                collect_classes(&rule.items, classes);
+            }
+            Item::AtRule(AtRule {
+                name,
+                block: Some(items),
+                ..
+            }) if !is_keyframes(name) => collect_classes(items, classes),
+            _ => {}
+        }
+    }
+}
+
+fn collect_keyframes(items: &[Item], keyframes: &mut Vec<String>) {
+    for item in items {
+        match item {
+            Item::Rule(rule) => collect_keyframes(&rule.items, keyframes),
+            Item::AtRule(at_rule) if is_keyframes(&at_rule.name) => {
+                keyframes.push(at_rule.prelude.clone());
+            }
+            Item::AtRule(AtRule {
+                block: Some(items), ..
+            }) => collect_keyframes(items, keyframes),
+            _ => {}
+        }
+    }
+}
+
+/// `keyframes` or a vendor prefixed version such as `-webkit-keyframes`
+fn is_keyframes(at_rule_name: &str) -> bool {
+    at_rule_name.ends_with("keyframes")
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Replace the name of every class selector, leaving strings and attribute selectors such as
+/// `[href$=".pdf"]` alone. `map` also gets where the name starts in the selector.
+fn map_class_names<F: FnMut(&str, usize) -> String>(selector: &str, mut map: F) -> String {
+    let mut mapped = String::new();
+    let mut chars = selector.char_indices().peekable();
+    let mut quote = None;
+    let mut in_attribute = false;
+
+    while let Some((idx, c)) = chars.next() {
+        mapped.push(c);
+
+        match (c, quote) {
+            (_, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('"', None) | ('\'', None) => quote = Some(c),
+            ('[', None) => in_attribute = true,
+            (']', None) => in_attribute = false,
+            ('.', None) if !in_attribute => {
+                let start = idx + 1;
+                let mut end = start;
+                while let Some(&(idx, c)) = chars.peek() {
+                    if !is_name_char(c) {
+                        break;
+                    }
+                    end = idx + c.len_utf8();
+                    chars.next();
+                }
+
+                // Not a class, such as the `.5` in `:nth-child(2.5)`
+                let name = &selector[start..end];
+                match name.chars().next() {
+                    Some(first) if !first.is_numeric() => mapped.push_str(&map(name, start)),
+                    _ => mapped.push_str(name),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    mapped
+}
+
+/// Replace every word in a value such as `fade 1s ease-in`
+fn map_words<F: Fn(&str) -> String>(value: &str, map: F) -> String {
+    let mut mapped = String::new();
+    let mut word = String::new();
+
+    for c in value.chars() {
+        if is_name_char(c) {
+            word.push(c);
+            continue;
+        }
+
+        mapped.push_str(&map(&word));
+        word.clear();
+        mapped.push(c);
+    }
+    mapped.push_str(&map(&word));
+
+    mapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser;
+
+    #[test]
+    fn module_classes_are_local() {
+        let mut stylesheet = parser::parse(
+            r#"
+            .list {
+                animation: fade 1s;
+
+                > .list-item:hover, a[href$=".pdf"] { color: red; }
+            }
+
+            @keyframes fade {
+                from { opacity: 0; }
+            }
+            "#,
+        )
+        .unwrap();
+        stylesheet.module = true;
+
+        let classes = local_classes(&stylesheet)
+            .into_iter()
+            .map(|(class, _)| class)
+            .collect::<Vec<String>>();
+        assert_eq!(classes, vec!["list", "list-item"]);
+        assert_eq!(
+            stylesheet.to_css("app", &[]),
+            r#".app_list {
+    animation: app_fade 1s;
+}
+.app_list > .app_list-item:hover, .app_list a[href$=".pdf"] {
+    color: red;
+}
+@keyframes app_fade {
+    from {
+        opacity: 0;
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn host_keeps_classes_global() {
+        let stylesheet = parser::parse(
+            r#"
+            :host {
+                animation: spin 2s, other 1s;
+                .red { color: red; }
+            }
+
+            @keyframes spin {
+                to { transform: rotate(360deg); }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert!(local_classes(&stylesheet).is_empty());
+        assert_eq!(
+            stylesheet.to_css("app", &[]),
+            r#".app {
+    animation: app_spin 2s, other 1s;
+}
+.app .red {
+    color: red;
+}
+@keyframes app_spin {
+    to {
+        transform: rotate(360deg);
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn module_errors() {
+        let error_at = |css: &str, interpolations: bool| {
+            let mut stylesheet = parser::parse(css).unwrap();
+            stylesheet.module = true;
+
+            let error = check_module(&stylesheet, interpolations).unwrap_err();
+            (error.offset, error.message)
+        };
+
+        assert_eq!(
+            error_at(".list { > :host { color: red; } }", false),
+            (
+                10,
+                "`:host` can't be used in a `module` css! call, where every class is local. \
+                 Style one of its classes instead."
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            error_at("div { color: red; }", false).1,
+            "a `module` css! call returns a field for each of its classes, but there aren't any"
+        );
+        assert_eq!(
+            error_at(".list { color: red; } .type { color: blue; }", false),
+            (
+                23,
+                "`.type` can't be a field of the struct that css! returns since `type` is a Rust \
+                 keyword"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            error_at(".list-item, .list_item { color: red; }", false),
+            (
+                13,
+                "`.list-item` and `.list_item` would both be the `list_item` field of the struct \
+                 that css! returns"
+                    .to_string()
+            )
+        );
+        assert_eq!(error_at(".style { color: red; }", true).0, 1);
+
+        let stylesheet = parser::parse(".style { color: red; }").unwrap();
+        assert_eq!(check_module(&stylesheet, true), Ok(()));
+    }
+
+    #[test]
+    fn field_names() {
+        assert_eq!(field_name("list-item"), "list_item");
+    }
+}
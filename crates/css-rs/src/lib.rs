@@ -0,0 +1,86 @@
+//! css-rs is the runtime half of css-rs-macro's `runtime` setting.
+//!
+//! With `runtime = true` in a crate's `[package.metadata.css-rs]` every `css!` call in it
+//! embeds its CSS in your binary and returns a `Css` instead of a `&'static str`. The first
+//! time a `Css` is rendered its CSS is registered:
+//!
+//! - In the browser it gets injected into a `<style>` element in the document's `<head>`.
+//! - On the server it gets remembered so that `take_style_tags` can return the `<style>` tags
+//!   for just the CSS that the page used.
+//!
+//! So your components are styled without an `OUTPUT_CSS` build step, and server rendered
+//! pages come with their critical CSS.
+//!
+//! ```rust,ignore
+//! static NAV_BAR_CSS: Css = css! {"
+//! :host { display: flex; }
+//! "};
+//!
+//! let html = html! { <div class=NAV_BAR_CSS></div> }.to_string();
+//! let styles = css_rs::take_style_tags();
+//! ```
+
+#![deny(missing_docs)]
+
+use std::fmt;
+use std::ops::Deref;
+
+mod registry;
+pub use self::registry::take_style_tags;
+
+/// A class name from a `css!` call along with the CSS that it needs.
+///
+/// Displaying a `Css`, which is what `html!` does with `class=NAV_BAR_CSS`, registers its
+/// CSS. Dereferencing it to a `&str` does not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Css {
+    class: &'static str,
+    scope: &'static str,
+    css: &'static str,
+}
+
+impl Css {
+    /// Used by `css!`. `scope` is the class that the whole `css!` call was scoped to, which
+    /// is what we register its CSS under.
+    #[doc(hidden)]
+    pub const fn new(class: &'static str, scope: &'static str, css: &'static str) -> Css {
+        Css { class, scope, css }
+    }
+
+    /// Register our CSS and return our class name
+    pub fn class(&self) -> &'static str {
+        registry::register(self.scope, self.css);
+        self.class
+    }
+
+    /// All of the CSS from our `css!` call
+    pub fn css(&self) -> &'static str {
+        self.css
+    }
+}
+
+impl fmt::Display for Css {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.class())
+    }
+}
+
+impl Deref for Css {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.class
+    }
+}
+
+impl PartialEq<str> for Css {
+    fn eq(&self, other: &str) -> bool {
+        self.class == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Css {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.class == *other
+    }
+}
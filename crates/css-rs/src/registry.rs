@@ -0,0 +1,114 @@
+//! Keeping track of which `css!` calls have been rendered.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+thread_local! {
+    /// The scopes whose CSS we've injected into the document, or that have been used since
+    /// the last `take_style_tags` on the server
+    static REGISTERED: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new());
+
+    /// The `<style>` tags for the CSS used since the last `take_style_tags`, in the order
+    /// that it was first used
+    #[cfg(not(target_arch = "wasm32"))]
+    static STYLE_TAGS: RefCell<String> = RefCell::new(String::new());
+}
+
+/// Register the CSS for a `css!` call, unless it already has been.
+pub(crate) fn register(scope: &'static str, css: &'static str) {
+    let is_new = REGISTERED.with(|registered| registered.borrow_mut().insert(scope));
+
+    if is_new {
+        add_style(scope, css);
+    }
+}
+
+/// `<style>` tags with the CSS from every `css!` call that was rendered on this thread since
+/// the last time that this was called, such as `<style data-css-rs="_css_rs_5e1c2b0a">...`.
+///
+/// Call this after rendering a page on the server and put the tags in its `<head>`. In the
+/// browser, CSS that is already in a `<style>` tag from the server doesn't get injected again.
+///
+/// Always empty in the browser, where CSS gets injected as soon as it is used.
+pub fn take_style_tags() -> String {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        REGISTERED.with(|registered| registered.borrow_mut().clear());
+        STYLE_TAGS.with(|tags| tags.replace(String::new()))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        String::new()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn add_style(scope: &'static str, css: &'static str) {
+    STYLE_TAGS.with(|tags| {
+        tags.borrow_mut().push_str(&format!(
+            r#"<style data-css-rs="{}">{}</style>"#,
+            scope,
+            escape(css)
+        ))
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+fn add_style(scope: &'static str, css: &'static str) {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .expect("No document");
+
+    // The server might have already rendered it
+    let selector = format!(r#"style[data-css-rs="{}"]"#, scope);
+    if let Ok(Some(_)) = document.query_selector(&selector) {
+        return;
+    }
+
+    let style = document
+        .create_element("style")
+        .expect("Could not create <style>");
+    let _ = style.set_attribute("data-css-rs", scope);
+    style.set_text_content(Some(css));
+
+    if let Some(head) = document.head() {
+        let _ = head.append_child(&style);
+    }
+}
+
+/// Make sure that the CSS can't end its `<style>` element early
+#[cfg(not(target_arch = "wasm32"))]
+fn escape(css: &str) -> String {
+    css.replace("</", "<\\/")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Css;
+
+    #[test]
+    fn style_tags_for_used_css() {
+        static NAV: Css = Css::new("nav", "nav", ".nav { color: red; }");
+        static NAV_ITEM: Css = Css::new("nav_item", "nav", ".nav { color: red; }");
+        static FOOTER: Css = Css::new("footer", "footer", ".footer { content: '</style>'; }");
+        static UNUSED: Css = Css::new("unused", "unused", ".unused { color: blue; }");
+
+        assert_eq!(&*UNUSED, "unused");
+        assert_eq!(format!("{} {} {}", NAV, NAV_ITEM, NAV), "nav nav_item nav");
+        assert_eq!(FOOTER.to_string(), "footer");
+
+        assert_eq!(
+            crate::take_style_tags(),
+            r#"<style data-css-rs="nav">.nav { color: red; }</style><style data-css-rs="footer">.footer { content: '<\/style>'; }</style>"#
+        );
+
+        // Every page gets the styles that it uses
+        assert_eq!(crate::take_style_tags(), "");
+        NAV.to_string();
+        assert_eq!(
+            crate::take_style_tags(),
+            r#"<style data-css-rs="nav">.nav { color: red; }</style>"#
+        );
+    }
+}
@@ -2,7 +2,9 @@
 #![cfg(test)]
 
 use html_macro::{html, text};
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use virtual_node::{VirtualNode, VElement};
 
 struct HtmlMacroTest<'a> {
@@ -46,7 +48,7 @@ fn one_prop() {
 #[test]
 fn event() {
     HtmlMacroTest {
-        desc: "Events are ignored in non wasm-32 targets",
+        desc: "Events are ignored when comparing nodes",
         generated: html! {
             <div onclick=|_: u8|{}></div>
         },
@@ -55,6 +57,19 @@ fn event() {
     .test();
 }
 
+#[test]
+fn event_handler_callable_in_non_wasm32_targets() {
+    let clicked = Rc::new(Cell::new(false));
+    let clicked_clone = Rc::clone(&clicked);
+
+    let node = html! {
+        <div onclick=move |_: u8| { clicked_clone.set(true) }></div>
+    };
+    node.trigger_event("click", 0_u8);
+
+    assert!(clicked.get(), "Event handlers can be called with a simulated event");
+}
+
 #[test]
 fn child_node() {
     let mut expected = VElement::new("div");
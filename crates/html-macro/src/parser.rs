@@ -88,6 +88,14 @@ impl HtmlParser {
                                   #var_name_node.as_velement_mut().expect("Not an element")
                                       .events.0.insert(#key.to_string(), closure_rc);
                                 }
+
+                                // Outside of the browser we store the closure as is so that
+                                // unit tests can call it with a simulated event.
+                                #[cfg(not(target_arch = "wasm32"))]
+                                {
+                                  #var_name_node.as_velement_mut().expect("Not an element")
+                                      .events.insert_handler(#key, #value);
+                                }
                             };
 
                             tokens.push(add_closure);
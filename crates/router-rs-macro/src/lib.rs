@@ -1,82 +1,382 @@
-//! Work in progress.. not finished..
+//! Macros for creating `router_rs::Route`s from functions.
+//!
+//! You'll usually use these through their re-exports in `router_rs`.
 
 extern crate proc_macro;
-#[macro_use]
-extern crate quote;
-#[macro_use]
-extern crate syn;
-use proc_macro2::*;
 
-use self::proc_macro::TokenStream;
-use syn::token::Token;
-use syn::{parse_macro_input, DeriveInput};
-
-use std::collections::HashSet;
-
-use quote::{ToTokens, TokenStreamExt};
-use syn::parse::{Error, Parse, ParseStream, Result as SynResult};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Result as SynResult};
 use syn::punctuated::Punctuated;
-use syn::{Expr, Field, Fields, Ident, ItemFn, Local, Pat, Stmt};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, FnArg, GenericArgument, Ident, ItemFn, LitStr, Pat, PathArguments};
+use syn::{Path, Token, Type};
 
 /// Parsed attributes from a `#[route(..)]`.
-#[derive(Default, Debug)]
+#[derive(Default)]
 struct RouteAttrs {
-    attrs: Vec<RouteAttr>,
+    path: Option<LitStr>,
+    name: Option<LitStr>,
+    guards: Vec<Path>,
 }
 
 impl Parse for RouteAttrs {
     fn parse(input: ParseStream) -> SynResult<Self> {
-        if input.is_empty() {
-            return Ok(RouteAttrs { attrs: vec![] });
-        }
+        let mut route_attrs = RouteAttrs::default();
 
-        let opts = syn::punctuated::Punctuated::<_, syn::token::Comma>::parse_terminated(input)?;
+        let attrs = Punctuated::<RouteAttr, Token![,]>::parse_terminated(input)?;
 
-        Ok(RouteAttrs {
-            attrs: opts.into_iter().collect(),
-        })
+        for attr in attrs {
+            match attr {
+                RouteAttr::Path(path) => route_attrs.path = Some(path),
+                RouteAttr::Name(name) => route_attrs.name = Some(name),
+                RouteAttr::Guard(guard) => route_attrs.guards.push(guard),
+            }
+        }
+
+        Ok(route_attrs)
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
 enum RouteAttr {
-    Path,
+    /// path = "/users/:id"
+    Path(LitStr),
+    /// name = "user"
+    Name(LitStr),
+    /// guard = require_sign_in
+    Guard(Path),
 }
 
 impl Parse for RouteAttr {
     fn parse(input: ParseStream) -> SynResult<Self> {
-        let original = input.fork();
         let attr: Ident = input.parse()?;
-        eprintln!("attr = {:#?}", attr);
+        input.parse::<Token![=]>()?;
 
         if attr == "path" {
-            println!("WORKED");
-            return Ok(RouteAttr::Path);
+            return Ok(RouteAttr::Path(input.parse()?));
+        }
+        if attr == "name" {
+            return Ok(RouteAttr::Name(input.parse()?));
+        }
+        if attr == "guard" {
+            return Ok(RouteAttr::Guard(input.parse()?));
         }
 
-        println!("NO WORK");
-
-        Err(original.error("unknown attribute"))
+        Err(syn::Error::new(attr.span(), "unknown route attribute"))
     }
 }
 
+/// Turn a function that renders a `VirtualNode` into a route.
+///
+/// Every argument of the function is either one of the path's parameters, in which case its
+/// `ParamType` is inferred from the argument's type, or a `Provided<T>` that comes from
+/// `Router::provide`.
+///
+/// The route is named after the function unless you give it a `name`, and you can create
+/// URLs for it with `Router::url_for`.
+///
+/// Each `guard` is a `fn(&Params) -> Option<Redirect>` that gets checked before rendering,
+/// i.e. `#[route(path = "/settings", guard = require_sign_in)]`.
+///
+/// ```rust,ignore
+/// #[route(path = "/users/:id", name = "user")]
+/// fn user_view(id: u32, store: Provided<RefCell<Store>>) -> VirtualNode {
+///     html! { <div> { store.borrow().user_name(id) } </div> }
+/// }
+///
+/// let router = Router::new(create_routes![user_view]);
+/// assert_eq!(router.url_for("user", &[("id", &5)]), Ok("/users/5".to_string()));
+/// ```
 #[proc_macro_attribute]
 pub fn route(args: TokenStream, input: TokenStream) -> TokenStream {
-    //    let input = parse_macro_input!(input as Token![struct]);
+    let args = parse_macro_input!(args as RouteAttrs);
+    let route_fn = parse_macro_input!(input as ItemFn);
+
+    match expand_route(args, &route_fn) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => {
+            let err = err.to_compile_error();
+            TokenStream::from(quote! { #route_fn #err })
+        }
+    }
+}
+
+fn expand_route(args: RouteAttrs, route_fn: &ItemFn) -> SynResult<TokenStream2> {
+    let path = match args.path {
+        Some(path) => path,
+        None => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                r#"missing route path, i.e. #[route(path = "/users/:id")]"#,
+            ));
+        }
+    };
+
+    let fn_name = &route_fn.ident;
+    let vis = &route_fn.vis;
+    let route_creator = route_creator_ident(fn_name);
+    let route_name = match args.name {
+        Some(name) => name.value(),
+        None => fn_name.to_string(),
+    };
+    let guards = &args.guards;
 
-    let mut args = parse_macro_input!(args as RouteAttrs);
+    let mut path_params = path_params(&path.value());
 
-    return input.into();
+    let mut param_types = vec![];
+    let mut arg_values = vec![];
 
-    eprintln!("args = {:#?}", args);
+    for arg in route_fn.decl.inputs.iter() {
+        let (arg_name, ty) = match arg {
+            FnArg::Captured(arg) => match &arg.pat {
+                Pat::Ident(pat) => (pat.ident.to_string(), &arg.ty),
+                pat => {
+                    return Err(syn::Error::new(
+                        pat.span(),
+                        "route arguments must be named after a path parameter",
+                    ));
+                }
+            },
+            arg => {
+                return Err(syn::Error::new(
+                    arg.span(),
+                    "routes can only take path parameters and Provided<T> arguments",
+                ));
+            }
+        };
 
-    TokenStream::from(quote!())
+        if let Some(provided) = provided_type(ty) {
+            let missing = format!(
+                "{} needs a Provided<{}>. Call router.provide(..) with one.",
+                fn_name,
+                quote!(#provided)
+            );
+            arg_values.push(quote! {
+                params.provided::<#provided>().expect(#missing)
+            });
+            continue;
+        }
+
+        let optional = match path_params.iter().position(|param| param.name == arg_name) {
+            Some(idx) => path_params.remove(idx).optional,
+            None => {
+                return Err(syn::Error::new(
+                    arg.span(),
+                    format!(
+                        "`{}` is not a parameter in route path \"{}\"",
+                        arg_name,
+                        path.value()
+                    ),
+                ));
+            }
+        };
+
+        let arg_value = match (optional, option_type(ty)) {
+            (true, Some(ty)) => {
+                let param_type = param_type(ty);
+                param_types.push(quote! {
+                    param_types.insert(#arg_name.to_string(), #param_type);
+                });
+
+                quote! { params.get::<#ty>(#arg_name).ok() }
+            }
+            (false, None) => {
+                let param_type = param_type(ty);
+                param_types.push(quote! {
+                    param_types.insert(#arg_name.to_string(), #param_type);
+                });
+
+                quote! {
+                    params.get::<#ty>(#arg_name).expect("Route params are validated before creating views")
+                }
+            }
+            (true, None) => {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    format!("`{}` is optional in the route path, so it must be an Option", arg_name),
+                ));
+            }
+            (false, Some(_)) => {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    format!("`{}` is always in the route path, so it can't be an Option", arg_name),
+                ));
+            }
+        };
+        arg_values.push(arg_value);
+    }
+
+    if let Some(unused) = path_params.first() {
+        return Err(syn::Error::new(
+            path.span(),
+            format!("path parameter `{}` is not an argument of {}", unused.name, fn_name),
+        ));
+    }
+
+    Ok(quote! {
+        #route_fn
+
+        #[doc(hidden)]
+        #vis fn #route_creator() -> router_rs::Route {
+            let mut param_types = ::std::collections::HashMap::new();
+            #(#param_types)*
+
+            router_rs::Route::new(
+                #path,
+                param_types,
+                Box::new(|params: router_rs::Params| {
+                    Box::new(router_rs::__private::RenderFn(move || {
+                        #fn_name(#(#arg_values),*)
+                    })) as Box<router_rs::__private::View>
+                }),
+            )
+            .with_name(#route_name)
+            #(.with_guard(Box::new(#guards)))*
+        }
+    })
+}
+
+/// Create a `Vec<Route>` from functions annotated with `#[route(..)]`.
+///
+/// ```rust,ignore
+/// let routes = create_routes![home_view, users::user_view];
+/// ```
+#[proc_macro]
+pub fn create_routes(input: TokenStream) -> TokenStream {
+    let routes = parse_macro_input!(input as RouteFns);
+
+    let route_creators = routes.0.into_iter().map(|mut route_fn| {
+        let last = route_fn.segments.last_mut().unwrap().into_value();
+        last.ident = route_creator_ident(&last.ident);
+        route_fn
+    });
+
+    let tokens = quote! {
+        vec![ #(#route_creators()),* ]
+    };
+
+    tokens.into()
+}
+
+/// user_view, users::profile_view
+struct RouteFns(Punctuated<Path, Token![,]>);
+
+impl Parse for RouteFns {
+    fn parse(input: ParseStream) -> SynResult<Self> {
+        Ok(RouteFns(Punctuated::parse_terminated(input)?))
+    }
+}
+
+/// The function that `#[route]` generates next to a route function in order to create
+/// its `Route`.
+fn route_creator_ident(route_fn: &Ident) -> Ident {
+    Ident::new(&format!("__percy_route_{}", route_fn), route_fn.span())
+}
+
+/// A named parameter in a route path
+#[derive(Debug, PartialEq)]
+struct PathParam {
+    name: String,
+    /// `:slug?` can be left off of the path
+    optional: bool,
+}
+
+/// "/users/:id/:slug?/*rest" -> [id, slug (optional), rest]
+fn path_params(path: &str) -> Vec<PathParam> {
+    path.split('/')
+        .filter_map(|segment| {
+            if segment.starts_with(':') && segment.ends_with('?') {
+                Some(PathParam {
+                    name: segment[1..segment.len() - 1].to_string(),
+                    optional: true,
+                })
+            } else if segment.starts_with(':') || (segment.starts_with('*') && segment.len() > 1) {
+                Some(PathParam {
+                    name: segment[1..].to_string(),
+                    optional: false,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// If this is an `Option<T>`, get `T`
+fn option_type(ty: &Type) -> Option<&Type> {
+    generic_type(ty, "Option")
+}
+
+/// If this is a `Provided<T>`, get `T`
+fn provided_type(ty: &Type) -> Option<&Type> {
+    generic_type(ty, "Provided")
+}
+
+/// If this is a `wrapper<T>` such as `Option<T>`, get `T`
+fn generic_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let type_path = match ty {
+        Type::Path(type_path) => type_path,
+        _ => return None,
+    };
+
+    let last = type_path.path.segments.iter().last()?;
+    if last.ident != wrapper {
+        return None;
+    }
+
+    match &last.arguments {
+        PathArguments::AngleBracketed(generics) if generics.args.len() == 1 => {
+            match generics.args.iter().next() {
+                Some(GenericArgument::Type(provided)) => Some(provided),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The `ParamType` that matches path segments that can be parsed into `ty`
+fn param_type(ty: &Type) -> TokenStream2 {
+    let type_name = match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            type_path.path.segments.iter().last().map(|last| last.ident.to_string())
+        }
+        _ => None,
+    };
+
+    match type_name.as_ref().map(|name| name.as_str()) {
+        Some("String") => quote! { router_rs::ParamType::String },
+        Some("i32") => quote! { router_rs::ParamType::I32 },
+        Some("i64") => quote! { router_rs::ParamType::I64 },
+        Some("u32") => quote! { router_rs::ParamType::U32 },
+        Some("u64") => quote! { router_rs::ParamType::U64 },
+        Some("f32") => quote! { router_rs::ParamType::F32 },
+        Some("f64") => quote! { router_rs::ParamType::F64 },
+        Some("bool") => quote! { router_rs::ParamType::Bool },
+        _ => quote! { router_rs::ParamType::custom::<#ty>() },
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
-    fn it_works() {
-        assert_eq!(2 + 2, 4);
+    fn path_params_without_colon() {
+        let param = |name: &str, optional| PathParam {
+            name: name.to_string(),
+            optional,
+        };
+
+        assert_eq!(
+            path_params("/users/:id/posts/:post_id"),
+            vec![param("id", false), param("post_id", false)]
+        );
+        assert_eq!(
+            path_params("/docs/:version?/*rest"),
+            vec![param("version", true), param("rest", false)]
+        );
+        assert_eq!(path_params("/*"), vec![]);
     }
 }
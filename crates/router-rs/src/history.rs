@@ -0,0 +1,379 @@
+//! Keeping the browser's URL and history in sync with a web app's path.
+//!
+//! Only works in the browser, but it compiles for every target so that apps that use it can
+//! be built and tested natively.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event, EventTarget, HtmlAnchorElement, MouseEvent, PopStateEvent, Window};
+
+/// Where in the browser's URL we keep the app's path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryMode {
+    /// `https://example.com/users/5`. Your server needs to render the app for every path.
+    Path,
+    /// `https://example.com/#/users/5`. For static hosting, where the server only knows
+    /// about `/index.html`.
+    Hash,
+}
+
+/// Listens to the browser's back and forward buttons and to clicks on links, and calls
+/// `on_change` with the new path whenever the app should navigate.
+///
+/// Clicks on same-origin `<a>` elements are handled without reloading the page, unless a
+/// modifier key is held or the link has a `target` or `download` attribute.
+///
+/// Scroll positions are saved for each history entry and restored when the user goes back
+/// or forward to it. New pages start scrolled to the top.
+///
+/// ```rust,ignore
+/// let store = Rc::clone(&app.store);
+/// let history = History::new(
+///     HistoryMode::Path,
+///     Box::new(move |path: &str| store.borrow_mut().dispatch(Msg::Path(path.to_string()))),
+/// );
+///
+/// history.navigate("/contributors");
+/// ```
+///
+/// Dropping the `History` stops listening.
+pub struct History {
+    shared: Rc<Shared>,
+    listeners: Vec<Listener>,
+}
+
+struct Shared {
+    mode: HistoryMode,
+    window: Window,
+    on_change: Box<Fn(&str)>,
+    scroll_positions: RefCell<ScrollPositions>,
+}
+
+/// The scroll position of every history entry that we've left.
+///
+/// Each entry that we create gets a key in its state. By the time that a popstate event tells
+/// us that the user went back or forward the browser has already switched entries, so we
+/// can't save anything into the entry that they left, but we do remember its key.
+struct ScrollPositions {
+    /// Keeps our keys from clashing with the keys from before the page was reloaded
+    session: String,
+    next_key: u32,
+    /// The key of the entry that we're on
+    current_key: String,
+    positions: HashMap<String, (f64, f64)>,
+}
+
+/// An event listener that gets removed when we're dropped
+struct Listener {
+    target: EventTarget,
+    event: &'static str,
+    closure: Closure<FnMut(Event)>,
+}
+
+impl History {
+    /// Start listening to history changes and link clicks.
+    pub fn new(mode: HistoryMode, on_change: Box<Fn(&str)>) -> History {
+        let window = web_sys::window().expect("No window");
+        let document = window.document().expect("No document");
+
+        // We restore scroll positions ourselves after the new page renders
+        let mut key = None;
+        if let Ok(history) = window.history() {
+            let _ = history.set_scroll_restoration(web_sys::ScrollRestoration::Manual);
+            key = history.state().ok().and_then(|state| state.as_string());
+        }
+
+        let session = window
+            .performance()
+            .map(|performance| performance.time_origin())
+            .unwrap_or(0.);
+
+        let shared = Rc::new(Shared {
+            mode,
+            window,
+            on_change,
+            scroll_positions: RefCell::new(ScrollPositions::new(&session.to_string())),
+        });
+        // The page was just loaded, so the browser has already scrolled
+        shared.enter_entry(key);
+
+        let on_popstate = {
+            let shared = Rc::clone(&shared);
+            Closure::wrap(Box::new(move |event: Event| {
+                let key = event
+                    .dyn_ref::<PopStateEvent>()
+                    .and_then(|event| event.state().as_string());
+
+                shared.leave_entry();
+                let scroll = shared.enter_entry(key);
+
+                (shared.on_change)(&shared.current_path());
+                if let Some(scroll) = scroll {
+                    shared.scroll_after_render(scroll);
+                }
+            }) as Box<FnMut(_)>)
+        };
+
+        let on_click = {
+            let shared = Rc::clone(&shared);
+            Closure::wrap(Box::new(move |event: Event| {
+                if let Some(path) = shared.intercepted_link(&event) {
+                    event.prevent_default();
+                    shared.navigate(&path, false);
+                }
+            }) as Box<FnMut(_)>)
+        };
+
+        let window: EventTarget = shared.window.clone().into();
+
+        let mut history = History {
+            shared,
+            listeners: vec![],
+        };
+        history.listen(window, "popstate", on_popstate);
+        history.listen(document.into(), "click", on_click);
+
+        history
+    }
+
+    /// Push a new entry onto the browser's history and navigate to `path`, such as
+    /// `/users/5?tab=posts`.
+    pub fn navigate(&self, path: &str) {
+        self.shared.navigate(path, false);
+    }
+
+    /// Navigate to `path` by replacing the current history entry, so that the back button
+    /// skips over the current page. Useful for redirects.
+    pub fn replace(&self, path: &str) {
+        self.shared.navigate(path, true);
+    }
+
+    /// The app's path from the browser's current URL, such as `/users/5?tab=posts`
+    pub fn current_path(&self) -> String {
+        self.shared.current_path()
+    }
+
+    fn listen(&mut self, target: EventTarget, event: &'static str, closure: Closure<FnMut(Event)>) {
+        target
+            .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+            .expect("Could not add event listener");
+
+        self.listeners.push(Listener {
+            target,
+            event,
+            closure,
+        });
+    }
+}
+
+impl Drop for History {
+    fn drop(&mut self) {
+        for listener in self.listeners.iter() {
+            let _ = listener.target.remove_event_listener_with_callback(
+                listener.event,
+                listener.closure.as_ref().unchecked_ref(),
+            );
+        }
+    }
+}
+
+impl Shared {
+    fn navigate(&self, path: &str, replace: bool) {
+        let history = self.window.history().expect("No history");
+
+        let url = match self.mode {
+            HistoryMode::Path => path.to_string(),
+            HistoryMode::Hash => format!("#{}", path),
+        };
+
+        // Remember where we were so that we can scroll back there when the user presses the
+        // back button
+        if !replace {
+            self.leave_entry();
+        }
+
+        let key = JsValue::from_str(&self.scroll_positions.borrow_mut().new_key());
+        if replace {
+            let _ = history.replace_state_with_url(&key, "", Some(&url));
+        } else {
+            let _ = history.push_state_with_url(&key, "", Some(&url));
+        }
+
+        (self.on_change)(&self.current_path());
+        self.scroll_after_render((0., 0.));
+    }
+
+    /// Save the scroll position of the entry that we're on, before we go to another one
+    fn leave_entry(&self) {
+        let scroll = (
+            self.window.scroll_x().unwrap_or(0.),
+            self.window.scroll_y().unwrap_or(0.),
+        );
+
+        self.scroll_positions.borrow_mut().leave(scroll);
+    }
+
+    /// Start keeping track of the entry that the browser is on, whose state is `key`. Entries
+    /// that we didn't create, such as the first one, get a key now. Returns the scroll position
+    /// that we saved when we left this entry before, or the top of the page for an entry of
+    /// ours that we never left. For other entries we leave scrolling up to the browser.
+    fn enter_entry(&self, key: Option<String>) -> Option<(f64, f64)> {
+        let mut scroll_positions = self.scroll_positions.borrow_mut();
+
+        match key {
+            Some(key) => Some(scroll_positions.enter(key)),
+            None => {
+                let key = scroll_positions.new_key();
+                if let Ok(history) = self.window.history() {
+                    let _ = history.replace_state(&JsValue::from_str(&key), "");
+                }
+                scroll_positions.enter(key);
+
+                None
+            }
+        }
+    }
+
+    fn current_path(&self) -> String {
+        let location = self.window.location();
+
+        match self.mode {
+            HistoryMode::Path => format!(
+                "{}{}{}",
+                location.pathname().unwrap_or_default(),
+                location.search().unwrap_or_default(),
+                location.hash().unwrap_or_default()
+            ),
+            HistoryMode::Hash => {
+                let hash = location.hash().unwrap_or_default();
+                let path = hash.trim_start_matches('#');
+
+                if path.starts_with('/') {
+                    path.to_string()
+                } else {
+                    format!("/{}", path)
+                }
+            }
+        }
+    }
+
+    /// Views usually re-render on the next animation frame after a path change, so we wait
+    /// until then to scroll.
+    fn scroll_after_render(&self, (x, y): (f64, f64)) {
+        let window = self.window.clone();
+        let scroll = Closure::once_into_js(move || window.scroll_to_with_x_and_y(x, y));
+
+        let _ = self.window.request_animation_frame(scroll.unchecked_ref());
+    }
+
+    /// If this click was on a same-origin link that we should handle without reloading the
+    /// page, the path to navigate to.
+    fn intercepted_link(&self, event: &Event) -> Option<String> {
+        let click = event.dyn_ref::<MouseEvent>()?;
+
+        let modified = click.ctrl_key() || click.meta_key() || click.shift_key() || click.alt_key();
+        if event.default_prevented() || click.button() != 0 || modified {
+            return None;
+        }
+
+        let anchor = event
+            .target()?
+            .dyn_into::<Element>()
+            .ok()?
+            .closest("a")
+            .ok()??
+            .dyn_into::<HtmlAnchorElement>()
+            .ok()?;
+
+        if anchor.has_attribute("download") || !anchor.target().is_empty() {
+            return None;
+        }
+
+        let location = self.window.location();
+        if anchor.origin() != location.origin().ok()? {
+            return None;
+        }
+
+        // Let the browser handle links to an element on the current page
+        let same_page = anchor.pathname() == location.pathname().ok()?
+            && anchor.search() == location.search().ok()?;
+        if same_page && !anchor.hash().is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "{}{}{}",
+            anchor.pathname(),
+            anchor.search(),
+            anchor.hash()
+        ))
+    }
+}
+
+impl ScrollPositions {
+    fn new(session: &str) -> ScrollPositions {
+        ScrollPositions {
+            session: session.to_string(),
+            next_key: 0,
+            current_key: String::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// A key for a new history entry, which becomes the current one
+    fn new_key(&mut self) -> String {
+        self.next_key += 1;
+        self.current_key = format!("{}:{}", self.session, self.next_key);
+
+        self.current_key.clone()
+    }
+
+    /// The user is leaving the current entry while scrolled to `scroll`
+    fn leave(&mut self, scroll: (f64, f64)) {
+        self.positions.insert(self.current_key.clone(), scroll);
+    }
+
+    /// The user went back or forward to the entry with this key. Where they were scrolled to
+    /// when they left it, or else the top of the page.
+    fn enter(&mut self, key: String) -> (f64, f64) {
+        let scroll = self.positions.get(&key).cloned().unwrap_or((0., 0.));
+        self.current_key = key;
+
+        scroll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scroll_positions() {
+        let mut scroll_positions = ScrollPositions::new("1234.5");
+
+        let first = scroll_positions.new_key();
+        assert_eq!(first, "1234.5:1");
+
+        // Navigating to a new page
+        scroll_positions.leave((0., 250.));
+        let second = scroll_positions.new_key();
+
+        // Back
+        scroll_positions.leave((10., 500.));
+        assert_eq!(scroll_positions.enter(first.clone()), (0., 250.));
+
+        // Forward
+        scroll_positions.leave((0., 300.));
+        assert_eq!(scroll_positions.enter(second), (10., 500.));
+
+        // Back again
+        scroll_positions.leave((0., 0.));
+        assert_eq!(scroll_positions.enter(first), (0., 300.));
+
+        // An entry from before the page was reloaded
+        assert_eq!(scroll_positions.enter("1000:3".to_string()), (0., 0.));
+    }
+}
@@ -2,15 +2,60 @@
 
 #![feature(proc_macro_hygiene)]
 
+// So that the code that our macros generate can refer to `router_rs` from within this crate too
+extern crate self as router_rs;
+
+pub use router_rs_macro::{create_routes, route};
+
 #[deny(missing_docs)]
 mod router;
-pub use self::router::Router;
+pub use self::router::{LayoutFn, NotFoundFn, Router};
+
+mod history;
+pub use self::history::{History, HistoryMode};
+
+mod params;
+pub use self::params::{ParamError, Params};
+
+mod provided;
+pub use self::provided::Provided;
+
+mod resolved;
+pub use self::resolved::{Redirect, Resolved};
 
 mod route;
-pub use self::route::Route;
+pub use self::route::{GuardFn, ParamType, Route};
+
+mod segment;
+
+mod url;
+pub use self::url::{Url, UrlError};
+
+/// Used by the code that `#[route]` generates
+#[doc(hidden)]
+pub mod __private {
+    pub use virtual_dom_rs::View;
+    use virtual_dom_rs::VirtualNode;
+
+    /// A `View` that renders by calling a route's function
+    pub struct RenderFn<F>(pub F);
+
+    impl<F: Fn() -> VirtualNode> View for RenderFn<F> {
+        fn render(&self) -> VirtualNode {
+            (self.0)()
+        }
+    }
+}
 
 /// Things that you'll usually need when working with frontend routing
 pub mod prelude {
+    pub use crate::ParamType;
+    pub use crate::Params;
+    pub use crate::Provided;
+    pub use crate::Redirect;
+    pub use crate::Resolved;
     pub use crate::Route;
     pub use crate::Router;
+    pub use crate::Url;
+    pub use crate::{create_routes, route};
 }
@@ -0,0 +1,174 @@
+//! The parameters that were extracted from a path, such as `id` in `/users/:id`
+
+use crate::provided::ProvidedMap;
+use crate::{Provided, Url};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The parameters of a route, keyed by their name without the leading `:`, along with the
+/// query parameters of the incoming URL.
+///
+/// For a route defined as `/users/:id` and an incoming path `/users/5?tab=posts`
+///
+/// ```rust,ignore
+/// assert_eq!(params.get::<u32>("id"), Ok(5));
+/// assert_eq!(params.query::<String>("tab"), Ok("posts".to_string()));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Params {
+    params: HashMap<String, String>,
+    url: Url,
+    provided: ProvidedMap,
+}
+
+/// An error when getting a parameter from `Params`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    /// The route does not have a parameter with this name
+    Missing { name: String },
+    /// The parameter's value could not be parsed into the type that was asked for
+    Invalid { name: String, value: String },
+}
+
+impl Params {
+    /// Create params from parameter names (without the leading `:`) and their values
+    pub fn new(params: HashMap<String, String>) -> Params {
+        Params {
+            params,
+            url: Url::default(),
+            provided: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn set_url(&mut self, url: Url) {
+        self.url = url;
+    }
+
+    pub(crate) fn set_provided(&mut self, provided: ProvidedMap) {
+        self.provided = provided;
+    }
+
+    /// Parse the route parameter with this name into any type that implements `FromStr`.
+    ///
+    /// When the parameter has a `ParamType` for `T` in the route's `param_types` this can
+    /// only fail if you ask for a name that the route does not define, since the route
+    /// wouldn't have matched a value that doesn't parse.
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T, ParamError> {
+        let value = self.get_str(name).ok_or_else(|| ParamError::Missing {
+            name: name.to_string(),
+        })?;
+
+        value.parse().map_err(|_| ParamError::Invalid {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// The raw value of the route parameter with this name
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(|value| value.as_str())
+    }
+
+    /// Parse the first query parameter with this name into any type that implements `FromStr`.
+    pub fn query<T: FromStr>(&self, name: &str) -> Result<T, ParamError> {
+        let value = self.query_str(name).ok_or_else(|| ParamError::Missing {
+            name: name.to_string(),
+        })?;
+
+        value.parse().map_err(|_| ParamError::Invalid {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// The decoded value of the first query parameter with this name
+    pub fn query_str(&self, name: &str) -> Option<&str> {
+        self.url.query_param(name)
+    }
+
+    /// The incoming URL that these params came from, including its query and fragment
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// The value of type `T` that was given to `Router::provide`, if any
+    pub fn provided<T: 'static>(&self) -> Option<Provided<T>> {
+        let value = self.provided.get(&TypeId::of::<T>())?;
+
+        Some(Provided::new(value.clone().downcast::<T>().ok()?))
+    }
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamError::Missing { name } => write!(f, "No parameter named {}", name),
+            ParamError::Invalid { name, value } => {
+                write!(f, "Invalid value {:?} for parameter {}", value, name)
+            }
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> Params {
+        let mut params = HashMap::new();
+        params.insert("id".to_string(), "5".to_string());
+        params.insert("name".to_string(), "alice".to_string());
+        Params::new(params)
+    }
+
+    #[test]
+    fn get() {
+        assert_eq!(params().get::<u32>("id"), Ok(5));
+        assert_eq!(params().get::<String>("name"), Ok("alice".to_string()));
+        assert_eq!(params().get_str("name"), Some("alice"));
+    }
+
+    #[test]
+    fn query() {
+        let mut params = params();
+        params.set_url(Url::parse("/users/5?page=2&sort=name"));
+
+        assert_eq!(params.query::<u32>("page"), Ok(2));
+        assert_eq!(params.query_str("sort"), Some("name"));
+        assert_eq!(
+            params.query::<u32>("sort"),
+            Err(ParamError::Invalid {
+                name: "sort".to_string(),
+                value: "name".to_string()
+            })
+        );
+        assert_eq!(
+            params.query::<u32>("id"),
+            Err(ParamError::Missing {
+                name: "id".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn get_errors() {
+        assert_eq!(
+            params().get::<u32>("age"),
+            Err(ParamError::Missing {
+                name: "age".to_string()
+            })
+        );
+        assert_eq!(
+            params().get::<u32>("name"),
+            Err(ParamError::Invalid {
+                name: "name".to_string(),
+                value: "alice".to_string()
+            })
+        );
+    }
+}
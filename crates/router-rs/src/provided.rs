@@ -0,0 +1,49 @@
+//! Values that every route can use, such as your application's state store.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::rc::Rc;
+
+/// Everything that was given to `Router::provide`, keyed by its type
+pub(crate) type ProvidedMap = HashMap<TypeId, Rc<dyn Any>>;
+
+/// A value that was given to `Router::provide`.
+///
+/// Routes created with `#[route]` can take a `Provided<T>` argument in order to get access to
+/// the value of type `T` that was provided to the router.
+///
+/// ```rust,ignore
+/// router.provide(Rc::clone(&store));
+///
+/// #[route(path = "/")]
+/// fn home_view(store: Provided<RefCell<Store>>) -> VirtualNode {
+///     HomeView::new(Rc::clone(&store)).render()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Provided<T> {
+    value: Rc<T>,
+}
+
+impl<T> Provided<T> {
+    pub(crate) fn new(value: Rc<T>) -> Provided<T> {
+        Provided { value }
+    }
+}
+
+impl<T> Deref for Provided<T> {
+    type Target = Rc<T>;
+
+    fn deref(&self) -> &Rc<T> {
+        &self.value
+    }
+}
+
+impl<T> Clone for Provided<T> {
+    fn clone(&self) -> Self {
+        Provided {
+            value: Rc::clone(&self.value),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! What a router decided to do with an incoming path.
+
+use virtual_dom_rs::View;
+
+/// The outcome of `Router::resolve`. Server side rendering can use this to pick the
+/// response's status code.
+///
+/// ```rust,ignore
+/// match router.resolve(path) {
+///     Resolved::View(view) => respond(200, view.render()),
+///     Resolved::NotFound(view) => respond(404, view.render()),
+///     Resolved::Redirect(redirect) => redirect_to(redirect.status, &redirect.path),
+/// }
+/// ```
+pub enum Resolved {
+    /// A route matched and its guards let it through
+    View(Box<View>),
+    /// No route matched. This is the router's not found view.
+    NotFound(Box<View>),
+    /// A redirect route matched, or a guard redirected
+    Redirect(Redirect),
+}
+
+/// Go to a different path instead of rendering a view
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirect {
+    /// Where to go, such as `/login?next=%2Fadmin`
+    pub path: String,
+    /// The HTTP status code to use when redirecting on the server, such as 302
+    pub status: u16,
+}
+
+impl Redirect {
+    /// A temporary redirect (302 Found)
+    pub fn found(path: &str) -> Redirect {
+        Redirect {
+            path: path.to_string(),
+            status: 302,
+        }
+    }
+
+    /// A permanent redirect (301 Moved Permanently)
+    pub fn permanent(path: &str) -> Redirect {
+        Redirect {
+            path: path.to_string(),
+            status: 301,
+        }
+    }
+}
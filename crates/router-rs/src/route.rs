@@ -1,24 +1,121 @@
+use crate::segment::{self, fill_segments, match_segments, Rank, Segment};
+use crate::{Params, Redirect, Url, UrlError};
 use std::collections::HashMap;
+use std::mem;
+use std::str::FromStr;
 use virtual_dom_rs::View;
 
-type ViewFn = Box<Fn(HashMap<String, String>) -> Box<View>>;
-type ParamTypes = HashMap<String, ParamType>;
+type ViewFn = Box<Fn(Params) -> Box<View>>;
+/// Decides whether or not a route can be shown, i.e. redirecting to a login page when the
+/// user isn't signed in. Returning `None` lets the route through.
+pub type GuardFn = Box<Fn(&Params) -> Option<Redirect>>;
+pub(crate) type ParamTypes = HashMap<String, ParamType>;
 
 /// A route specifies a path to match against. When a match is found a `view_creator` is used
 /// to return an `impl View` that can be used to render the appropriate content for that route.
+///
+/// Route definitions are made up of `/` separated segments:
+///
+/// - `users` only matches `users`
+/// - `:id` matches any segment that is valid for the `ParamType` of `id`
+/// - `:slug?` is the same but can be left off, i.e. `/posts/:id/:slug?`
+/// - `*rest` matches all of the remaining segments, i.e. `/docs/*rest`. `rest` gets captured
+///   as a parameter, such as `guide/routing` for `/docs/guide/routing`. A bare `*` matches
+///   without capturing, so `/*` matches every path.
 pub struct Route {
+    name: Option<String>,
     route_definition: &'static str,
+    segments: Vec<Segment>,
     param_types: ParamTypes,
-    view_creator: ViewFn,
+    handler: Handler,
+    guards: Vec<GuardFn>,
+}
+
+/// What happens when a route matches
+enum Handler {
+    View(ViewFn),
+    /// Go to another path. Parameters in `to`, such as `:id` in `/users/:id`, are filled in
+    /// with the params that the route captured.
+    Redirect { to: &'static str, status: u16 },
 }
 
 /// All of the parameters that our routes can have. This is how we would distinguish "id" in
 /// /users/:id
 /// from being a u32, u8, or some other value
-#[cfg_attr(test, derive(PartialEq, Debug))]
+///
+/// Parameters that don't have a `ParamType` in a route's `param_types` are treated as
+/// `ParamType::String`.
+#[derive(Debug, Clone, Copy)]
 pub enum ParamType {
+    /// Matches any segment
+    String,
+    I32,
+    I64,
     U32,
     U64,
+    F32,
+    F64,
+    /// `true` or `false`
+    Bool,
+    /// A hyphenated UUID such as `936da01f-9abd-4d9d-80c7-02af85c822a8`
+    Uuid,
+    /// Matches segments that the function returns `true` for. You'll usually create this using
+    /// `ParamType::custom`.
+    Custom(fn(&str) -> bool),
+}
+
+impl ParamType {
+    /// Whether every value that is valid for `other` is valid for this type too. We can't
+    /// tell for different custom types, so they never cover each other.
+    pub(crate) fn covers(&self, other: &ParamType) -> bool {
+        match (self, other) {
+            (ParamType::String, _) => true,
+            (ParamType::Custom(this), ParamType::Custom(other)) => *this as usize == *other as usize,
+            (ParamType::Custom(_), _) | (_, ParamType::Custom(_)) => false,
+            (this, other) => mem::discriminant(this) == mem::discriminant(other),
+        }
+    }
+
+    /// A parameter that matches any segment that can be parsed into `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// param_types.insert("color".to_string(), ParamType::custom::<Color>());
+    /// ```
+    pub fn custom<T: FromStr>() -> ParamType {
+        ParamType::Custom(|value| value.parse::<T>().is_ok())
+    }
+
+    /// Whether or not it is possible to convert the String that the user provided
+    /// into this parameter type
+    pub fn is_valid(&self, value: &str) -> bool {
+        match self {
+            ParamType::String => true,
+            ParamType::I32 => value.parse::<i32>().is_ok(),
+            ParamType::I64 => value.parse::<i64>().is_ok(),
+            ParamType::U32 => value.parse::<u32>().is_ok(),
+            ParamType::U64 => value.parse::<u64>().is_ok(),
+            ParamType::F32 => value.parse::<f32>().is_ok(),
+            ParamType::F64 => value.parse::<f64>().is_ok(),
+            ParamType::Bool => value.parse::<bool>().is_ok(),
+            ParamType::Uuid => is_uuid(value),
+            ParamType::Custom(is_valid) => is_valid(value),
+        }
+    }
+}
+
+/// 8-4-4-4-12 hex digits, i.e. `936da01f-9abd-4d9d-80c7-02af85c822a8`
+fn is_uuid(value: &str) -> bool {
+    let groups = value.split('-').collect::<Vec<&str>>();
+
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12].iter())
+            .all(|(group, len)| {
+                group.len() == *len && group.chars().all(|c| c.is_ascii_hexdigit())
+            })
 }
 
 impl Route {
@@ -30,9 +127,30 @@ impl Route {
         view_creator: ViewFn,
     ) -> Route {
         Route {
+            name: None,
             route_definition,
+            segments: Segment::parse_route_definition(route_definition),
             param_types,
-            view_creator,
+            handler: Handler::View(view_creator),
+            guards: vec![],
+        }
+    }
+
+    /// A route that redirects to another path instead of rendering a view. Parameters in the
+    /// target are filled in with the ones that this route captured, and the incoming query
+    /// string is kept unless the target has its own.
+    ///
+    /// ```rust,ignore
+    /// router.add_route(Route::redirect("/u/:id", "/users/:id", 301));
+    /// ```
+    pub fn redirect(route_definition: &'static str, to: &'static str, status: u16) -> Route {
+        Route {
+            name: None,
+            route_definition,
+            segments: Segment::parse_route_definition(route_definition),
+            param_types: HashMap::new(),
+            handler: Handler::Redirect { to, status },
+            guards: vec![],
         }
     }
 }
@@ -45,59 +163,164 @@ impl Route {
     /// ```rust,ignore
     /// route.matches("/food/tacos");
     /// ```
+    ///
+    /// Any query string or fragment is ignored, so this also matches "/food/tacos?spicy=true".
     pub fn matches(&self, path: &str) -> bool {
-        // ex: [ "", "food", ":food_type" ]
-        let defined_segments = self
-            .route_definition
-            .split("/")
-            .filter(|segment| segment.len() > 0)
-            .collect::<Vec<&str>>();
+        self.match_params(&Url::parse(path)).is_some()
+    }
 
-        // ex: [ "", "food", "tacos" ]
-        let incoming_segments = path
-            .split("/")
-            .filter(|segment| segment.len() > 0)
-            .collect::<Vec<&str>>();
+    /// The path that this route was defined with, i.e. `/users/:id`
+    pub fn route_definition(&self) -> &'static str {
+        self.route_definition
+    }
+
+    /// Check this guard before creating the route's view. Guards are checked in the order that
+    /// they were added, after the guards of the route's routers.
+    pub fn with_guard(mut self, guard: GuardFn) -> Route {
+        self.guards.push(guard);
+        self
+    }
 
-        if defined_segments.len() != incoming_segments.len() {
-            return false;
+    /// Where to go instead of creating this route's view, if one of its guards says so or if
+    /// it is a redirect route.
+    pub(crate) fn redirect_for(&self, params: &Params) -> Option<Redirect> {
+        for guard in self.guards.iter() {
+            if let Some(redirect) = guard(params) {
+                return Some(redirect);
+            }
         }
 
-        for (index, defined_segment) in defined_segments.iter().enumerate() {
-            if defined_segment.len() == 0 {
-                continue;
+        let (to, status) = match &self.handler {
+            Handler::View(_) => return None,
+            Handler::Redirect { to, status } => (Url::parse(to), *status),
+        };
+
+        let mut segments = vec![];
+        for segment in to.segments() {
+            let param = match segment.chars().next() {
+                Some(':') | Some('*') => params.get_str(&segment[1..]),
+                _ => None,
+            };
+
+            match param {
+                Some(value) => segments.extend(
+                    value
+                        .split('/')
+                        .filter(|segment| !segment.is_empty())
+                        .map(|segment| segment.to_string()),
+                ),
+                None => segments.push(segment.to_string()),
             }
+        }
 
-            let mut chars = defined_segment.chars();
+        let query = if to.query().is_empty() {
+            params.url().query()
+        } else {
+            to.query()
+        };
+
+        let mut url = Url::from_segments(segments);
+        for (name, value) in query {
+            url = url.with_query(name, value);
+        }
+        if let Some(fragment) = to.fragment() {
+            url = url.with_fragment(fragment);
+        }
 
-            let first_char = chars.next().unwrap();
+        Some(Redirect {
+            path: url.to_string(),
+            status,
+        })
+    }
 
-            // ex: ":food_type"
-            if first_char == ':' {
-                let param_name = chars.collect::<String>();
-                // ex: ParamType::String
-                let param_type = self.param_types.get(&param_name).unwrap();
+    /// How specific this route is, compared to other routes that match the same path
+    pub(crate) fn specificity(&self) -> Vec<Rank> {
+        let mut specificity = segment::specificity(&self.segments, &self.param_types);
+        specificity.push(Rank::End);
+        specificity
+    }
 
-                let incoming_param_value = incoming_segments[index];
+    /// Whether this route matches every path that `other` matches, going by their route
+    /// definitions.
+    pub(crate) fn covers(&self, other: &Route) -> bool {
+        let string = ParamType::String;
+        let param_type = |route: &Route, name: &str| -> ParamType {
+            *route.param_types.get(name).unwrap_or(&string)
+        };
 
-                // Make sure that it is possible to convert the String that the user provided
-                // into the parameter type that we expect (u32, u8, i8, etc)
-                match param_type {
-                    ParamType::U32 => {
-                        if incoming_param_value.parse::<u32>().is_err() {
-                            return false;
-                        }
+        self.segments.len() == other.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(other.segments.iter())
+                .all(|(this, other_segment)| match (this, other_segment) {
+                    (Segment::Static(this), Segment::Static(other)) => this == other,
+                    (
+                        Segment::Param {
+                            name,
+                            optional: this_optional,
+                        },
+                        Segment::Param {
+                            name: other_name,
+                            optional: other_optional,
+                        },
+                    ) => {
+                        this_optional == other_optional
+                            && param_type(self, name).covers(&param_type(other, other_name))
                     }
-                    ParamType::U64 => {
-                        if incoming_param_value.parse::<u64>().is_err() {
-                            return false;
-                        }
+                    (Segment::CatchAll(Some(name)), Segment::CatchAll(other_name)) => {
+                        let other_type = match other_name {
+                            Some(other_name) => param_type(other, other_name),
+                            None => ParamType::String,
+                        };
+                        param_type(self, name).covers(&other_type)
                     }
-                };
+                    (Segment::CatchAll(None), Segment::CatchAll(_)) => true,
+                    _ => false,
+                })
+    }
+
+    /// Name this route so that you can create URLs for it with `Router::url_for`
+    pub fn with_name(mut self, name: &str) -> Route {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// The name that this route was given, if any
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|name| name.as_str())
+    }
+
+    /// Create a URL that matches this route by filling in its parameters. `prefixes` are the
+    /// paths of the routers that this route's router is mounted under, outermost first.
+    ///
+    /// Parameters that aren't in the route's path get added to the query string.
+    pub(crate) fn url(
+        &self,
+        prefixes: &[&[Segment]],
+        params: &[(&str, String)],
+    ) -> Result<Url, UrlError> {
+        let mut used = vec![false; params.len()];
+
+        let mut segments = vec![];
+        for prefix in prefixes {
+            segments.extend(fill_segments(prefix, &HashMap::new(), params, &mut used)?);
+        }
+        segments.extend(fill_segments(
+            &self.segments,
+            &self.param_types,
+            params,
+            &mut used,
+        )?);
+
+        let mut url = Url::from_segments(segments);
+        for (idx, (name, value)) in params.iter().enumerate() {
+            if !used[idx] {
+                url = url.with_query(name, value);
             }
         }
 
-        true
+        Ok(url)
     }
 
     /// Given an incoming path, create the `View` that uses that path data.
@@ -106,33 +329,54 @@ impl Route {
     /// and our incoming path is `/users/5`
     ///
     /// Our view will end up getting created with `id: 5`
+    ///
+    /// This doesn't check the route's guards, and panics if this is a redirect route.
+    /// `Router::resolve` handles both.
     pub fn view(&self, incoming_path: &str) -> Box<View> {
-        (self.view_creator)(self.params(incoming_path))
+        self.view_with_params(self.params(incoming_path))
     }
 
-    fn params(&self, incoming_path: &str) -> HashMap<String, String> {
-        let incoming_path = incoming_path.split("/").collect::<Vec<&str>>();
+    pub(crate) fn view_with_params(&self, params: Params) -> Box<View> {
+        match &self.handler {
+            Handler::View(view_creator) => view_creator(params),
+            Handler::Redirect { to, .. } => panic!(
+                "{} redirects to {} instead of creating a view",
+                self.route_definition, to
+            ),
+        }
+    }
 
-        self.route_definition
-            .split("/")
-            .collect::<Vec<&str>>()
+    pub(crate) fn params(&self, incoming_path: &str) -> Params {
+        let url = Url::parse(incoming_path);
+
+        let mut params = Params::new(self.match_params(&url).unwrap_or_default());
+        params.set_url(url);
+
+        params
+    }
+
+    /// The route params for this url, if it matches this route
+    pub(crate) fn match_params(&self, url: &Url) -> Option<HashMap<String, String>> {
+        // ex: [ "food", "tacos" ]
+        let incoming_segments = url
+            .segments()
             .iter()
-            .enumerate()
-            .filter(|(_index, segment)| {
-                if segment.len() == 0 {
-                    return false;
-                }
+            .map(|segment| segment.as_str())
+            .collect::<Vec<&str>>();
 
-                segment.chars().next().unwrap() == ':'
-            })
-            .map(|(index, segment)| (segment.to_string(), incoming_path[index].to_string()))
-            .collect::<HashMap<String, String>>()
+        self.match_segments(&incoming_segments)
+    }
+
+    /// The route params for these path segments, if they match this route
+    pub(crate) fn match_segments(&self, incoming: &[&str]) -> Option<HashMap<String, String>> {
+        match_segments(&self.segments, incoming, &self.param_types)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{create_routes, route, Provided, Resolved, Router};
     use std::cell::RefCell;
     use std::rc::Rc;
     use virtual_dom_rs::html;
@@ -158,9 +402,9 @@ mod tests {
 
     impl MatchRouteTestCase {
         fn test(&self) {
-            let view_creator = |params: HashMap<String, String>| {
+            let view_creator = |params: Params| {
                 Box::new(MyView {
-                    id: params.get(":id").unwrap().parse::<u32>().unwrap(),
+                    id: params.get("id").unwrap(),
                 }) as Box<View>
             };
 
@@ -204,6 +448,143 @@ mod tests {
         .test();
     }
 
+    #[test]
+    fn static_segments() {
+        MatchRouteTestCase {
+            desc: "Static segments have to match exactly",
+            route_definition: "/users/:id",
+            matches: vec![("/people/5", false, "people is not users")],
+        }
+        .test();
+    }
+
+    #[test]
+    fn optional_segments() {
+        MatchRouteTestCase {
+            desc: "Optional trailing segments",
+            route_definition: "/users/:id/:slug?",
+            matches: vec![
+                ("/users/5", true, "slug can be left off"),
+                ("/users/5/percy", true, "slug can be provided"),
+                ("/users/5/percy/more", false, "no more than the slug"),
+                ("/users", false, "id is required"),
+            ],
+        }
+        .test();
+    }
+
+    #[test]
+    fn catch_all_segments() {
+        MatchRouteTestCase {
+            desc: "Catch-all segments",
+            route_definition: "/users/:id/*rest",
+            matches: vec![
+                ("/users/5", true, "rest can be empty"),
+                ("/users/5/posts/10", true, "rest captures multiple segments"),
+                ("/users/foo/posts", false, "earlier segments are still typed"),
+            ],
+        }
+        .test();
+
+        MatchRouteTestCase {
+            desc: "404 catch-all",
+            route_definition: "/*",
+            matches: vec![("/", true, "root"), ("/any/path/at/all", true, "anything")],
+        }
+        .test();
+    }
+
+    #[test]
+    fn captured_params() {
+        let route = Route::new(
+            "/docs/:version?/*rest",
+            HashMap::new(),
+            Box::new(|_| Box::new(MyView { id: 0 }) as Box<View>),
+        );
+
+        let params = route.params("/docs/guide/routing");
+        assert_eq!(params.get_str("version"), Some("guide"));
+        assert_eq!(params.get_str("rest"), Some("routing"));
+
+        let mut param_types = HashMap::new();
+        param_types.insert("version".to_string(), ParamType::U32);
+        let route = Route::new(
+            "/docs/:version?/*rest",
+            param_types,
+            Box::new(|_| Box::new(MyView { id: 0 }) as Box<View>),
+        );
+
+        let params = route.params("/docs/guide/routing");
+        assert_eq!(params.get_str("version"), None);
+        assert_eq!(params.get_str("rest"), Some("guide/routing"));
+    }
+
+    #[test]
+    fn query_and_fragment() {
+        MatchRouteTestCase {
+            desc: "Query strings and fragments are not part of the path",
+            route_definition: "/users/:id",
+            matches: vec![
+                ("/users/5?tab=posts", true, "query string"),
+                ("/users/5#posts", true, "fragment"),
+                ("/users/5/?tab=posts#top", true, "both"),
+                ("/users/5/posts?tab=posts", false, "still checks the path"),
+            ],
+        }
+        .test();
+    }
+
+    #[test]
+    fn decoded_params() {
+        let route = Route::new(
+            "/search/:term",
+            HashMap::new(),
+            Box::new(|_| Box::new(MyView { id: 0 }) as Box<View>),
+        );
+
+        let params = route.params("/search/rust%20lang?page=2");
+        assert_eq!(params.get_str("term"), Some("rust lang"));
+        assert_eq!(params.query::<u32>("page"), Ok(2));
+    }
+
+    #[test]
+    fn url() {
+        let mut param_types = HashMap::new();
+        param_types.insert("id".to_string(), ParamType::U32);
+        let route = Route::new(
+            "/users/:id/:slug?/*rest",
+            param_types,
+            Box::new(|_| Box::new(MyView { id: 0 }) as Box<View>),
+        );
+
+        let url = |params: &[(&str, &str)]| {
+            let params = params
+                .iter()
+                .map(|(name, value)| (*name, value.to_string()))
+                .collect::<Vec<(&str, String)>>();
+            route.url(&[], &params).map(|url| url.to_string())
+        };
+
+        assert_eq!(url(&[("id", "5")]), Ok("/users/5".to_string()));
+        assert_eq!(
+            url(&[("id", "5"), ("slug", "percy rs"), ("rest", "a/b"), ("tab", "posts")]),
+            Ok("/users/5/percy%20rs/a/b?tab=posts".to_string())
+        );
+        assert_eq!(
+            url(&[("slug", "percy")]),
+            Err(UrlError::MissingParam {
+                name: "id".to_string()
+            })
+        );
+        assert_eq!(
+            url(&[("id", "five")]),
+            Err(UrlError::InvalidParam {
+                name: "id".to_string(),
+                value: "five".to_string()
+            })
+        );
+    }
+
     #[test]
     fn create_view() {
         assert_eq!(
@@ -213,10 +594,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn param_types() {
+        let cases = vec![
+            (ParamType::String, "anything", true),
+            (ParamType::I32, "-5", true),
+            (ParamType::I32, "5.5", false),
+            (ParamType::I64, "-9000000000", true),
+            (ParamType::U32, "-5", false),
+            (ParamType::U64, "9000000000", true),
+            (ParamType::F32, "5.5", true),
+            (ParamType::F64, "-0.25", true),
+            (ParamType::F64, "half", false),
+            (ParamType::Bool, "true", true),
+            (ParamType::Bool, "yes", false),
+            (ParamType::Uuid, "936da01f-9abd-4d9d-80c7-02af85c822a8", true),
+            (ParamType::Uuid, "936da01f-9abd-4d9d-80c7-02af85c822a", false),
+            (ParamType::Uuid, "936da01f9abd4d9d80c702af85c822a8", false),
+            (ParamType::custom::<char>(), "c", true),
+            (ParamType::custom::<char>(), "cc", false),
+        ];
+
+        for (param_type, value, is_valid) in cases {
+            assert_eq!(
+                param_type.is_valid(value),
+                is_valid,
+                "{:?} {}",
+                param_type,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn untyped_params_are_strings() {
+        let view_creator = |_| Box::new(MyView { id: 0 }) as Box<View>;
+        let route = Route::new("/users/:name", HashMap::new(), Box::new(view_creator));
+
+        assert!(route.matches("/users/alice"));
+        assert!(route.matches("/users/5"));
+    }
+
+    #[test]
+    fn params_are_named_without_colon() {
+        let params = create_test_route().params("/users/300");
+
+        assert_eq!(params.get::<u32>("id"), Ok(300));
+        assert_eq!(params.get_str(":id"), None);
+    }
+
     fn create_test_route() -> Route {
-        let view_creator = |params: HashMap<String, String>| {
+        let view_creator = |params: Params| {
             Box::new(MyView {
-                id: params.get(":id").unwrap().parse::<u32>().unwrap(),
+                id: params.get("id").unwrap(),
             }) as Box<View>
         };
 
@@ -228,26 +658,115 @@ mod tests {
         route
     }
 
-    // TODO:
+    #[route(path = "/users/:id")]
+    fn user_view(id: u32) -> VirtualNode {
+        MyView { id }.render()
+    }
+
     #[test]
     fn macro_works() {
-        //        let route = MyView::route();
-        //
-        //        assert!(route.matches("/users/5"));
-        //        assert!(!route.matches("/users/not_a_u32"));
+        let router = Router::new(create_routes![user_view]);
+
+        assert!(router.view("/users/not_a_u32").is_none());
+        assert_eq!(
+            router.view("/users/5").unwrap().render(),
+            html! {<div> 5 </div>}
+        );
     }
 
-    struct Store {}
+    #[route(path = "/docs/:version?/*page")]
+    fn docs_view(version: Option<u32>, page: String) -> VirtualNode {
+        let text = VirtualNode::text(&format!("{:?} {}", version, page));
+        html! { <div> {text} </div> }
+    }
 
-    // TODO: Plan out how to provide a state store to routes on paper. Probably some sort of generic
-    // StateStore<T> where T is your applications Store
-    //    #[route(path = "/users/:id")]
-    struct ViewWithStore {
-        id: u32,
-        store: Rc<RefCell<Store>>,
+    #[test]
+    fn macro_optional_and_catch_all() {
+        let router = Router::new(create_routes![docs_view]);
+
+        assert_eq!(
+            router
+                .view("/docs/2/guide/routing")
+                .unwrap()
+                .render()
+                .to_string(),
+            "<div>Some(2) guide/routing</div>"
+        );
+        assert_eq!(
+            router.view("/docs/guide").unwrap().render().to_string(),
+            "<div>None guide</div>"
+        );
+    }
+
+    #[route(path = "/posts/:id", name = "post")]
+    fn post_view(id: u32) -> VirtualNode {
+        MyView { id }.render()
+    }
+
+    #[test]
+    fn macro_names_routes() {
+        let routes = create_routes![user_view, post_view, docs_view];
+        assert_eq!(routes[0].name(), Some("user_view"));
+        assert_eq!(routes[1].name(), Some("post"));
+
+        let router = Router::new(routes);
+
+        assert_eq!(router.url_for("post", &[("id", &3)]), Ok("/posts/3".to_string()));
+        assert_eq!(
+            router.url_for("docs_view", &[("page", &"guide/routing")]),
+            Ok("/docs/guide/routing".to_string())
+        );
+    }
+
+    fn require_even_id(params: &Params) -> Option<Redirect> {
+        match params.get::<u32>("id") {
+            Ok(id) if id % 2 == 0 => None,
+            _ => Some(Redirect::found("/")),
+        }
+    }
+
+    #[route(path = "/even/:id", guard = require_even_id)]
+    fn even_view(id: u32) -> VirtualNode {
+        MyView { id }.render()
+    }
+
+    #[test]
+    fn macro_guards() {
+        let router = Router::new(create_routes![even_view]);
+
+        assert!(router.view("/even/2").is_some());
+        match router.resolve("/even/3") {
+            Resolved::Redirect(redirect) => assert_eq!(redirect, Redirect::found("/")),
+            _ => panic!("Should have redirected"),
+        }
+    }
+
+    struct Store {
+        name: &'static str,
+    }
+
+    #[route(path = "/users/:id/greeting")]
+    fn view_with_store(id: u32, store: Provided<RefCell<Store>>) -> VirtualNode {
+        let greeting = VirtualNode::text(&format!("{} {}", store.borrow().name, id));
+        html! { <div> {greeting} </div> }
     }
 
-    // TODO
     #[test]
-    fn provide_state_store() {}
+    fn provide_state_store() {
+        let store = Rc::new(RefCell::new(Store { name: "Percy" }));
+
+        let mut router = Router::new(create_routes![view_with_store]);
+        router.provide(Rc::clone(&store));
+
+        assert_eq!(
+            router.view("/users/5/greeting").unwrap().render(),
+            html! {<div> Percy 5 </div>}
+        );
+
+        store.borrow_mut().name = "Router";
+        assert_eq!(
+            router.view("/users/5/greeting").unwrap().render(),
+            html! {<div> Router 5 </div>}
+        );
+    }
 }
@@ -1,8 +1,19 @@
 //! Powers routing for frontend web applications
 
-use crate::Route;
+use crate::provided::ProvidedMap;
+use crate::segment::{self, match_segments, Rank, Segment};
+use crate::{GuardFn, Params, Resolved, Route, Url, UrlError};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::rc::Rc;
 use virtual_dom_rs::prelude::*;
 
+/// Wraps the view of whichever route matched, i.e. in order to render a nav bar around it.
+pub type LayoutFn = Box<Fn(Params, Box<View>) -> Box<View>>;
+
+/// Creates the view to show when no route matches.
+pub type NotFoundFn = Box<Fn(Params) -> Box<View>>;
+
 /// Holds all of the routes for an application.
 ///
 /// A typical use case is that when we want to move to a new route
@@ -10,39 +21,361 @@ use virtual_dom_rs::prelude::*;
 /// we'll query our router to see if the new route matches any of our route definitions.
 ///
 /// Then if we find a matching route we'll return it.
+///
+/// Larger applications can split their routes across several routers and `mount` them
+/// under a path prefix such as `/admin`.
 #[derive(Default)]
 pub struct Router {
     routes: Vec<Route>,
+    mounted: Vec<Mounted>,
+    layout: Option<LayoutFn>,
+    guards: Vec<GuardFn>,
+    not_found: Option<NotFoundFn>,
+    provided: ProvidedMap,
+}
+
+/// A router that handles every path under a prefix
+struct Mounted {
+    prefix_definition: String,
+    prefix: Vec<Segment>,
+    router: Router,
+}
+
+/// A route that matched an incoming path
+struct Match<'a> {
+    route: &'a Route,
+    /// The routers that the route was found in, outermost first
+    routers: Vec<&'a Router>,
+    /// Including the params captured by the prefixes of mounted routers
+    params: HashMap<String, String>,
+    specificity: Vec<Rank>,
 }
 
 impl Router {
-    /// Append a route to our vector of Route's. The order that you add routes matters, as
-    /// we'll start from the beginning of the vector when matching routes and return the
-    /// first route that matches.
+    /// Create a router from a vector of routes, usually from `create_routes![..]`.
+    ///
+    /// ```rust,ignore
+    /// let router = Router::new(create_routes![home_view, user_view]);
+    /// ```
+    pub fn new(routes: Vec<Route>) -> Router {
+        Router {
+            routes,
+            ..Router::default()
+        }
+    }
+
+    /// Append a route to our vector of Route's.
+    ///
+    /// When several routes match a path we use the most specific one, comparing their
+    /// segments from left to right. Static segments beat typed params, typed params beat
+    /// untyped ones and untyped params beat optional params and wildcards. So `/users/new`
+    /// beats `/users/:id` no matter which one was added first.
+    ///
+    /// Only when two matching routes are equally specific does the one that was added first win.
     pub fn add_route(&mut self, route: Route) {
         self.routes.push(route);
     }
 
-    /// Get the first route in our routes vector view that handles this `incoming_route`
-    /// and return the view for that route.
+    /// Handle every path under `prefix`, such as `/admin` or `/users/:id`, with another router.
+    /// Its routes match the rest of the path, i.e. `/settings` for `/admin/settings`.
+    ///
+    /// The mounted router's routes are ranked against our own as if the prefix was part of
+    /// their path. When they're equally specific our own routes win.
+    ///
+    /// Parameters in the prefix are matched as strings and get merged with the params of the
+    /// route that matched. Values that we `provide` are also available to the mounted router's
+    /// routes, unless it provides its own value of the same type.
+    ///
+    /// ```rust,ignore
+    /// let mut admin = Router::new(create_routes![settings_view, user_view]);
+    /// admin.set_layout(Box::new(|_params, view| Box::new(AdminLayout::new(view)) as Box<View>));
+    ///
+    /// router.mount("/admin", admin);
+    /// ```
+    pub fn mount(&mut self, prefix: &str, router: Router) {
+        self.mounted.push(Mounted {
+            prefix_definition: prefix.trim_end_matches('/').to_string(),
+            prefix: Segment::parse_route_definition(prefix),
+            router,
+        });
+    }
+
+    /// Wrap the view of any route that matches in this router, or in one of the routers
+    /// mounted under it, in a layout. The layout gets the same `Params` as the route.
+    ///
+    /// Layouts of mounted routers are wrapped in the layouts of the routers they're mounted
+    /// in, so the outermost router's layout is rendered outermost.
+    pub fn set_layout(&mut self, layout: LayoutFn) {
+        self.layout = Some(layout);
+    }
+
+    /// Check this guard before creating the view of any route in this router, or in one of the
+    /// routers mounted under it. Useful for requiring that the user is signed in for every
+    /// page under `/admin`.
+    ///
+    /// ```rust,ignore
+    /// admin.add_guard(Box::new(|params: &Params| {
+    ///     let store = params.provided::<RefCell<Store>>().unwrap();
+    ///
+    ///     if store.borrow().is_signed_in() {
+    ///         None
+    ///     } else {
+    ///         Some(Redirect::found("/login"))
+    ///     }
+    /// }));
+    /// ```
+    pub fn add_guard(&mut self, guard: GuardFn) {
+        self.guards.push(guard);
+    }
+
+    /// The view to show when no route matches, wrapped in our layout. Without one we render
+    /// a plain "Page not found" message.
+    pub fn set_not_found(&mut self, view_creator: NotFoundFn) {
+        self.not_found = Some(view_creator);
+    }
+
+    /// Give every route access to a value, such as your application's state store.
+    ///
+    /// Routes get it through a `Provided<T>` argument or `Params::provided`. Providing
+    /// another value of the same type replaces the first one.
+    pub fn provide<T: 'static>(&mut self, value: Rc<T>) {
+        self.provided.insert(TypeId::of::<T>(), value);
+    }
+
+    /// Create a URL for the route with this name, filling in its path parameters.
+    ///
+    /// Parameters that aren't part of the route's path are added to the query string.
+    ///
+    /// ```rust,ignore
+    /// // #[route(path = "/users/:id", name = "user")]
+    /// assert_eq!(router.url_for("user", &[("id", &5)]), Ok("/users/5".to_string()));
+    /// assert!(router.url_for("user", &[("id", &"five")]).is_err());
+    /// ```
+    pub fn url_for(&self, name: &str, params: &[(&str, &dyn ToString)]) -> Result<String, UrlError> {
+        let mut prefixes = vec![];
+        let route = self
+            .find_named(name, &mut prefixes)
+            .ok_or_else(|| UrlError::UnknownRoute {
+                name: name.to_string(),
+            })?;
+
+        let params = params
+            .iter()
+            .map(|(name, value)| (*name, value.to_string()))
+            .collect::<Vec<(&str, String)>>();
+
+        Ok(route.url(&prefixes, &params)?.to_string())
+    }
+
+    /// Find the route with this name in this router or any mounted router, pushing the
+    /// prefixes that it's mounted under onto `prefixes`.
+    fn find_named<'a>(&'a self, name: &str, prefixes: &mut Vec<&'a [Segment]>) -> Option<&'a Route> {
+        if let Some(route) = self.routes.iter().find(|route| route.name() == Some(name)) {
+            return Some(route);
+        }
+
+        for mounted in self.mounted.iter() {
+            prefixes.push(&mounted.prefix);
+
+            if let Some(route) = mounted.router.find_named(name, prefixes) {
+                return Some(route);
+            }
+
+            prefixes.pop();
+        }
+
+        None
+    }
+
+    /// Find the most specific route that handles this `incoming_route` (see `add_route`)
+    /// and return the view for that route, wrapped in any layouts.
     ///
     /// You'll typically call this when trying to render the correct view based on the
     /// page URL or after clicking on an anchor tag.
+    ///
+    /// `incoming_route` can include a query string and fragment, such as
+    /// `/search?q=percy#results`. Views get the query through `Params::query`.
+    ///
+    /// Returns `None` if no route matches or if the route redirects. Use `resolve` to find
+    /// out which.
     pub fn view(&self, incoming_route: &str) -> Option<Box<View>> {
+        match self.resolve(incoming_route) {
+            Resolved::View(view) => Some(view),
+            Resolved::NotFound(_) | Resolved::Redirect(_) => None,
+        }
+    }
+
+    /// Decide what to show for this `incoming_route`. Either the view of the most specific
+    /// route that matches, a redirect from a guard or a redirect route, or our not found view.
+    pub fn resolve(&self, incoming_route: &str) -> Resolved {
+        let url = Url::parse(incoming_route);
+
+        // ex: [ "food", "tacos" ]
+        let incoming_segments = url
+            .segments()
+            .iter()
+            .map(|segment| segment.as_str())
+            .collect::<Vec<&str>>();
+
+        let found = match self.find_match(&incoming_segments) {
+            Some(found) => found,
+            None => {
+                let params = create_params(HashMap::new(), url, &[self]);
+
+                let view = match &self.not_found {
+                    Some(not_found) => not_found(params.clone()),
+                    None => Box::new(NotFoundView) as Box<View>,
+                };
+
+                return Resolved::NotFound(wrap_in_layouts(&[self], &params, view));
+            }
+        };
+
+        let params = create_params(found.params, url, &found.routers);
+
+        for router in found.routers.iter() {
+            for guard in router.guards.iter() {
+                if let Some(redirect) = guard(&params) {
+                    return Resolved::Redirect(redirect);
+                }
+            }
+        }
+
+        if let Some(redirect) = found.route.redirect_for(&params) {
+            return Resolved::Redirect(redirect);
+        }
+
+        let view = found.route.view_with_params(params.clone());
+
+        Resolved::View(wrap_in_layouts(&found.routers, &params, view))
+    }
+
+    /// Find the most specific route that matches the `incoming` segments, in this router or
+    /// in one of our mounted routers.
+    fn find_match<'a>(&'a self, incoming: &[&str]) -> Option<Match<'a>> {
+        let mut best: Option<Match> = None;
+
+        let mut consider = |found: Match<'a>| {
+            let is_better = match &best {
+                Some(best) => found.specificity > best.specificity,
+                None => true,
+            };
+            if is_better {
+                best = Some(found);
+            }
+        };
+
         for route in self.routes.iter() {
-            if route.matches(incoming_route) {
-                return Some(route.view(incoming_route));
+            if let Some(params) = route.match_segments(incoming) {
+                consider(Match {
+                    route,
+                    routers: vec![self],
+                    params,
+                    specificity: route.specificity(),
+                });
             }
         }
 
-        None
+        for mounted in self.mounted.iter() {
+            for taken in 0..=incoming.len() {
+                let no_types = HashMap::new();
+
+                let mut params = match match_segments(&mounted.prefix, &incoming[..taken], &no_types) {
+                    Some(prefix_params) => prefix_params,
+                    None => continue,
+                };
+
+                if let Some(mut found) = mounted.router.find_match(&incoming[taken..]) {
+                    params.extend(found.params);
+                    found.params = params;
+
+                    found.routers.insert(0, self);
+
+                    let mut specificity = segment::specificity(&mounted.prefix, &no_types);
+                    specificity.extend(found.specificity);
+                    found.specificity = specificity;
+
+                    consider(found);
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The definitions of routes that can never be used because an equally specific route
+    /// that was added before them matches every path that they match, such as a second
+    /// `/users/:id`. Routes in mounted routers are listed with their prefix.
+    ///
+    /// Check this in one of your tests to catch routes that are shadowed by mistake.
+    ///
+    /// ```rust,ignore
+    /// assert_eq!(make_router().unreachable_routes(), Vec::<String>::new());
+    /// ```
+    pub fn unreachable_routes(&self) -> Vec<String> {
+        let mut unreachable = vec![];
+
+        for (idx, route) in self.routes.iter().enumerate() {
+            let shadowed = self.routes[..idx].iter().any(|earlier| {
+                earlier.specificity() == route.specificity() && earlier.covers(route)
+            });
+
+            if shadowed {
+                unreachable.push(route.route_definition().to_string());
+            }
+        }
+
+        for mounted in self.mounted.iter() {
+            for route_definition in mounted.router.unreachable_routes() {
+                unreachable.push(format!("{}{}", mounted.prefix_definition, route_definition));
+            }
+        }
+
+        unreachable
+    }
+}
+
+/// Route params, query params and the full url, along with everything that the routers
+/// (outermost first) provide.
+fn create_params(route_params: HashMap<String, String>, url: Url, routers: &[&Router]) -> Params {
+    let mut provided = ProvidedMap::new();
+    for router in routers.iter() {
+        for (type_id, value) in router.provided.iter() {
+            provided.insert(*type_id, Rc::clone(value));
+        }
+    }
+
+    let mut params = Params::new(route_params);
+    params.set_url(url);
+    params.set_provided(provided);
+
+    params
+}
+
+/// Wrap a view in the layouts of the routers (outermost first) that it was found in
+fn wrap_in_layouts(routers: &[&Router], params: &Params, mut view: Box<View>) -> Box<View> {
+    for router in routers.iter().rev() {
+        if let Some(layout) = &router.layout {
+            view = layout(params.clone(), view);
+        }
+    }
+
+    view
+}
+
+/// What we show when no route matches and there isn't a `Router::set_not_found` view
+struct NotFoundView;
+
+impl View for NotFoundView {
+    fn render(&self) -> VirtualNode {
+        html! { <div> Page not found </div> }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::route::ParamType;
+    use crate::{ParamType, Redirect};
     use std::collections::HashMap;
     use virtual_dom_rs::html;
 
@@ -81,4 +414,310 @@ mod tests {
             html! { <div> second </div>}
         );
     }
+
+    #[test]
+    fn url_for() {
+        let mut param_types = HashMap::new();
+        param_types.insert("id".to_string(), ParamType::U64);
+
+        let view_creator = Box::new(|_| Box::new(TestView { kind: "user" }) as Box<View>);
+        let route = Route::new("/users/:id", param_types, view_creator).with_name("user");
+
+        let router = Router::new(vec![route]);
+
+        assert_eq!(router.url_for("user", &[("id", &5)]), Ok("/users/5".to_string()));
+        assert_eq!(
+            router.url_for("user", &[("id", &5), ("tab", &"posts")]),
+            Ok("/users/5?tab=posts".to_string())
+        );
+        assert_eq!(
+            router.url_for("users", &[("id", &5)]),
+            Err(UrlError::UnknownRoute {
+                name: "users".to_string()
+            })
+        );
+        assert!(router.url_for("user", &[("id", &-5)]).is_err());
+    }
+
+    fn kind_route(route_definition: &'static str, id_type: ParamType, kind: &'static str) -> Route {
+        let mut param_types = HashMap::new();
+        param_types.insert("id".to_string(), id_type);
+
+        let view_creator = Box::new(move |_| Box::new(TestView { kind }) as Box<View>);
+        Route::new(route_definition, param_types, view_creator)
+    }
+
+    #[test]
+    fn most_specific_route_wins() {
+        let router = Router::new(vec![
+            kind_route("/*", ParamType::String, "anything"),
+            kind_route("/users/:name", ParamType::String, "name"),
+            kind_route("/users/:id", ParamType::U32, "id"),
+            kind_route("/users/:id/*", ParamType::U32, "id and more"),
+            kind_route("/users/new", ParamType::String, "new"),
+        ]);
+
+        let kind = |path| router.view(path).unwrap().render().to_string();
+
+        assert_eq!(kind("/users/new"), "<div>new</div>");
+        assert_eq!(kind("/users/5"), "<div>id</div>");
+        assert_eq!(kind("/users/alice"), "<div>name</div>");
+        assert_eq!(kind("/users/5/posts"), "<div>id and more</div>");
+        assert_eq!(kind("/posts"), "<div>anything</div>");
+    }
+
+    #[test]
+    fn equally_specific_routes_use_the_first() {
+        let router = Router::new(vec![
+            kind_route("/users/:id", ParamType::String, "first"),
+            kind_route("/users/:name", ParamType::String, "second"),
+        ]);
+
+        assert_eq!(
+            router.view("/users/5").unwrap().render().to_string(),
+            "<div>first</div>"
+        );
+    }
+
+    #[test]
+    fn mounted_routes_are_ranked() {
+        let mut users = Router::default();
+        users.add_route(kind_route("/new", ParamType::String, "new"));
+
+        let mut router = Router::new(vec![kind_route("/users/:id", ParamType::String, "id")]);
+        router.mount("/users", users);
+
+        assert_eq!(
+            router.view("/users/new").unwrap().render().to_string(),
+            "<div>new</div>"
+        );
+    }
+
+    #[test]
+    fn unreachable_routes() {
+        let mut admin = Router::default();
+        admin.add_route(kind_route("/*rest", ParamType::String, "rest"));
+        admin.add_route(kind_route("/*", ParamType::String, "anything"));
+
+        let mut router = Router::new(vec![
+            kind_route("/users/:name", ParamType::String, "name"),
+            kind_route("/users/:id", ParamType::U32, "id"),
+            kind_route("/users/:id", ParamType::U64, "u64 id"),
+            kind_route("/users/:user_id", ParamType::String, "user id"),
+            kind_route("/users/new", ParamType::String, "new"),
+        ]);
+        router.mount("/admin/", admin);
+
+        assert_eq!(
+            router.unreachable_routes(),
+            vec!["/users/:user_id".to_string(), "/admin/*".to_string()]
+        );
+    }
+
+    fn resolve(router: &Router, path: &str) -> String {
+        match router.resolve(path) {
+            Resolved::View(view) => format!("200 {}", view.render()),
+            Resolved::NotFound(view) => format!("404 {}", view.render()),
+            Resolved::Redirect(redirect) => format!("{} {}", redirect.status, redirect.path),
+        }
+    }
+
+    #[test]
+    fn not_found() {
+        let mut router = Router::new(vec![kind_route("/users/:id", ParamType::U32, "user")]);
+
+        assert_eq!(resolve(&router, "/users/5"), "200 <div>user</div>");
+        assert_eq!(resolve(&router, "/users/alice"), "404 <div>Page not found</div>");
+        assert!(router.view("/users/alice").is_none());
+
+        router.set_layout(layout("app"));
+        router.set_not_found(Box::new(|params: Params| {
+            Box::new(TextView(format!("No page at {}", params.url()))) as Box<View>
+        }));
+
+        assert_eq!(
+            resolve(&router, "/users/alice"),
+            "404 <main>app<div>No page at /users/alice</div></main>"
+        );
+    }
+
+    #[test]
+    fn redirect_routes() {
+        let router = Router::new(vec![
+            Route::redirect("/u/:id/*rest", "/users/:id/*rest", 301),
+            Route::redirect("/old", "/new?from=old#top", 302),
+        ]);
+
+        assert_eq!(resolve(&router, "/u/5/posts/3?tab=all"), "301 /users/5/posts/3?tab=all");
+        assert_eq!(resolve(&router, "/old?ignored=true"), "302 /new?from=old#top");
+    }
+
+    struct Session {
+        signed_in: bool,
+        admin: bool,
+    }
+
+    fn guarded_router(signed_in: bool, admin: bool) -> Router {
+        let require_admin = |params: &Params| {
+            if params.provided::<Session>().unwrap().admin {
+                None
+            } else {
+                Some(Redirect::found("/"))
+            }
+        };
+
+        let mut admin_router = Router::new(vec![
+            kind_route("/", ParamType::String, "admin"),
+            kind_route("/users/:id", ParamType::U32, "admin user")
+                .with_guard(Box::new(require_admin)),
+        ]);
+        admin_router.add_guard(Box::new(|params: &Params| {
+            if params.provided::<Session>().unwrap().signed_in {
+                None
+            } else {
+                let login = Url::new("/login").with_query("next", params.url());
+                Some(Redirect::found(&login.to_string()))
+            }
+        }));
+
+        let mut router = Router::new(vec![kind_route("/", ParamType::String, "home")]);
+        router.mount("/admin", admin_router);
+        router.provide(Rc::new(Session { signed_in, admin }));
+        router
+    }
+
+    #[test]
+    fn guards() {
+        let signed_out = guarded_router(false, false);
+        assert_eq!(resolve(&signed_out, "/"), "200 <div>home</div>");
+        assert_eq!(resolve(&signed_out, "/admin"), "302 /login?next=/admin");
+        assert_eq!(
+            resolve(&signed_out, "/admin/users/5"),
+            "302 /login?next=/admin/users/5"
+        );
+
+        let signed_in = guarded_router(true, false);
+        assert_eq!(resolve(&signed_in, "/admin"), "200 <div>admin</div>");
+        assert_eq!(resolve(&signed_in, "/admin/users/5"), "302 /");
+
+        let admin = guarded_router(true, true);
+        assert_eq!(resolve(&admin, "/admin/users/5"), "200 <div>admin user</div>");
+    }
+
+    /// Renders the text that it's created with
+    struct TextView(String);
+
+    impl View for TextView {
+        fn render(&self) -> VirtualNode {
+            let text = VirtualNode::text(&self.0);
+            html! {<div> { text } </div> }
+        }
+    }
+
+    /// Renders its title around the child view
+    struct LayoutView {
+        title: String,
+        child: Box<View>,
+    }
+
+    impl View for LayoutView {
+        fn render(&self) -> VirtualNode {
+            let title = VirtualNode::text(&self.title);
+            html! {<main> { title } { self.child.render() } </main> }
+        }
+    }
+
+    /// A route that renders the values of `param_names`
+    fn text_route(
+        route_definition: &'static str,
+        name: &str,
+        param_names: &'static [&'static str],
+    ) -> Route {
+        let view_creator = Box::new(move |params: Params| {
+            let text = param_names
+                .iter()
+                .map(|name| format!("{}={}", name, params.get_str(name).unwrap()))
+                .collect::<Vec<String>>();
+            Box::new(TextView(text.join(" "))) as Box<View>
+        });
+
+        Route::new(route_definition, HashMap::new(), view_creator).with_name(name)
+    }
+
+    fn layout(title: &'static str) -> LayoutFn {
+        Box::new(move |_, child| {
+            Box::new(LayoutView {
+                title: title.to_string(),
+                child,
+            }) as Box<View>
+        })
+    }
+
+    fn admin_router() -> Router {
+        let mut users = Router::new(vec![text_route("/posts/:post", "user_post", &["user", "post"])]);
+        users.set_layout(layout("user"));
+
+        let mut admin = Router::new(vec![text_route("/settings/:page", "settings", &["page"])]);
+        admin.mount("/users/:user", users);
+        admin.set_layout(layout("admin"));
+
+        let mut router = Router::new(vec![text_route("/", "home", &[])]);
+        router.mount("/admin", admin);
+        router
+    }
+
+    #[test]
+    fn mounted_routers() {
+        let router = admin_router();
+
+        assert_eq!(router.view("/").unwrap().render().to_string(), "<div></div>");
+        assert_eq!(
+            router.view("/admin/settings/users").unwrap().render().to_string(),
+            "<main>admin<div>page=users</div></main>"
+        );
+        assert_eq!(
+            router.view("/admin/users/alice/posts/3").unwrap().render().to_string(),
+            "<main>admin<main>user<div>user=alice post=3</div></main></main>"
+        );
+
+        assert!(router.view("/settings/users").is_none());
+        assert!(router.view("/admin/users/alice").is_none());
+    }
+
+    #[test]
+    fn url_for_mounted_routes() {
+        let router = admin_router();
+
+        assert_eq!(
+            router.url_for("user_post", &[("user", &"alice"), ("post", &3)]),
+            Ok("/admin/users/alice/posts/3".to_string())
+        );
+        assert_eq!(
+            router.url_for("user_post", &[("post", &3)]),
+            Err(UrlError::MissingParam {
+                name: "user".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn mounted_routers_inherit_provided_values() {
+        let view_creator = Box::new(|params: Params| {
+            let name = params.provided::<&'static str>().unwrap();
+            let count = params.provided::<u32>().unwrap();
+            Box::new(TextView(format!("{} {}", **name, **count))) as Box<View>
+        });
+        let mut child = Router::new(vec![Route::new("/", HashMap::new(), view_creator)]);
+        child.provide(Rc::new("child"));
+
+        let mut router = Router::default();
+        router.provide(Rc::new("parent"));
+        router.provide(Rc::new(5_u32));
+        router.mount("/child", child);
+
+        assert_eq!(
+            router.view("/child").unwrap().render().to_string(),
+            "<div>child 5</div>"
+        );
+    }
 }
@@ -0,0 +1,295 @@
+//! The pieces of a route definition such as `/users/:id/*rest`, and matching them against
+//! incoming paths.
+
+use crate::route::{ParamType, ParamTypes};
+use crate::UrlError;
+use std::collections::HashMap;
+
+/// One `/` separated piece of a route definition
+#[derive(Debug, PartialEq)]
+pub(crate) enum Segment {
+    /// `users` in `/users/:id`. Only matches that exact text.
+    Static(String),
+    /// `:id` in `/users/:id`, or `:slug?` in `/posts/:id/:slug?` when it is optional.
+    Param { name: String, optional: bool },
+    /// `*rest` in `/docs/*rest`. Matches all of the remaining segments, including none.
+    /// A bare `*` matches them without capturing them as a parameter.
+    CatchAll(Option<String>),
+}
+
+impl Segment {
+    /// "/users/:id/*rest" -> [Static("users"), Param("id"), CatchAll(Some("rest"))]
+    pub(crate) fn parse_route_definition(route_definition: &str) -> Vec<Segment> {
+        route_definition
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| {
+                if segment.starts_with(':') {
+                    let name = &segment[1..];
+
+                    if name.ends_with('?') {
+                        Segment::Param {
+                            name: name[..name.len() - 1].to_string(),
+                            optional: true,
+                        }
+                    } else {
+                        Segment::Param {
+                            name: name.to_string(),
+                            optional: false,
+                        }
+                    }
+                } else if segment.starts_with('*') {
+                    match &segment[1..] {
+                        "" => Segment::CatchAll(None),
+                        name => Segment::CatchAll(Some(name.to_string())),
+                    }
+                } else {
+                    Segment::Static(segment.to_string())
+                }
+            })
+            .collect()
+    }
+}
+
+/// How specific a segment is. When several routes match a path the route whose segments are
+/// the most specific, compared from left to right, is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Rank {
+    /// `*rest`
+    CatchAll,
+    /// `:slug?`
+    Optional,
+    /// The end of a route definition. A route that ends beats one that goes on with segments
+    /// that might not match anything, so `/users/:id` beats `/users/:id/*`.
+    End,
+    /// `:name` without a `ParamType`, or with `ParamType::String`
+    Untyped,
+    /// `:id` with a `ParamType` such as `ParamType::U32`
+    Typed,
+    /// `users`
+    Static,
+}
+
+/// The rank of each of these segments, not including the `Rank::End`
+pub(crate) fn specificity(defined: &[Segment], param_types: &ParamTypes) -> Vec<Rank> {
+    defined
+        .iter()
+        .map(|segment| match segment {
+            Segment::Static(_) => Rank::Static,
+            Segment::Param { optional: true, .. } => Rank::Optional,
+            Segment::Param { name, .. } => match param_types.get(name) {
+                None | Some(ParamType::String) => Rank::Untyped,
+                Some(_) => Rank::Typed,
+            },
+            Segment::CatchAll(_) => Rank::CatchAll,
+        })
+        .collect()
+}
+
+/// Match the segments of a route definition against the segments of an incoming path,
+/// returning the parameters that were captured.
+pub(crate) fn match_segments(
+    defined: &[Segment],
+    incoming: &[&str],
+    param_types: &ParamTypes,
+) -> Option<HashMap<String, String>> {
+    let mut params = HashMap::new();
+
+    if match_remaining(defined, incoming, param_types, &mut params) {
+        Some(params)
+    } else {
+        None
+    }
+}
+
+fn match_remaining(
+    defined: &[Segment],
+    incoming: &[&str],
+    param_types: &ParamTypes,
+    params: &mut HashMap<String, String>,
+) -> bool {
+    let (segment, defined_rest) = match defined.split_first() {
+        Some(segment) => segment,
+        // Routes should not match additional segments
+        None => return incoming.is_empty(),
+    };
+
+    match segment {
+        Segment::Static(text) => {
+            incoming.first() == Some(&text.as_str())
+                && match_remaining(defined_rest, &incoming[1..], param_types, params)
+        }
+        Segment::Param { name, optional } => {
+            if let Some(value) = incoming.first() {
+                if is_valid(param_types, name, value) {
+                    params.insert(name.to_string(), value.to_string());
+
+                    if match_remaining(defined_rest, &incoming[1..], param_types, params) {
+                        return true;
+                    }
+
+                    params.remove(name);
+                }
+            }
+
+            // An optional parameter that isn't there doesn't get captured
+            *optional && match_remaining(defined_rest, incoming, param_types, params)
+        }
+        Segment::CatchAll(name) => {
+            // Capture as much as we can while still letting the rest of the route match
+            for taken in (0..=incoming.len()).rev() {
+                let value = incoming[..taken].join("/");
+
+                if let Some(name) = name {
+                    if !is_valid(param_types, name, &value) {
+                        continue;
+                    }
+                    params.insert(name.to_string(), value);
+                }
+
+                if match_remaining(defined_rest, &incoming[taken..], param_types, params) {
+                    return true;
+                }
+
+                if let Some(name) = name {
+                    params.remove(name);
+                }
+            }
+
+            false
+        }
+    }
+}
+
+/// Fill in the parameters of a route definition in order to create the (not yet
+/// percent-encoded) segments of a path that it matches.
+///
+/// `used` gets marked for every one of the `params` that ended up in the path.
+pub(crate) fn fill_segments(
+    defined: &[Segment],
+    param_types: &ParamTypes,
+    params: &[(&str, String)],
+    used: &mut [bool],
+) -> Result<Vec<String>, UrlError> {
+    let mut find_param = |name: &str| -> Result<Option<&String>, UrlError> {
+        let idx = match params.iter().position(|(param, _)| *param == name) {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+        used[idx] = true;
+
+        let value = &params[idx].1;
+        if is_valid(param_types, name, value) {
+            Ok(Some(value))
+        } else {
+            Err(UrlError::InvalidParam {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+        }
+    };
+
+    let mut segments = vec![];
+
+    for segment in defined.iter() {
+        match segment {
+            Segment::Static(text) => segments.push(text.to_string()),
+            Segment::Param { name, optional } => match find_param(name)? {
+                Some(value) => segments.push(value.to_string()),
+                None if *optional => {}
+                None => {
+                    return Err(UrlError::MissingParam {
+                        name: name.to_string(),
+                    });
+                }
+            },
+            // The rest of the path can be empty, so we don't require it
+            Segment::CatchAll(Some(name)) => {
+                if let Some(rest) = find_param(name)? {
+                    segments.extend(
+                        rest.split('/')
+                            .filter(|segment| !segment.is_empty())
+                            .map(|segment| segment.to_string()),
+                    );
+                }
+            }
+            Segment::CatchAll(None) => {}
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Parameters that don't have a `ParamType` are strings
+fn is_valid(param_types: &ParamTypes, name: &str, value: &str) -> bool {
+    param_types
+        .get(name)
+        .unwrap_or(&ParamType::String)
+        .is_valid(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_route_definition() {
+        assert_eq!(
+            Segment::parse_route_definition("/docs/:version/:page?/*rest"),
+            vec![
+                Segment::Static("docs".to_string()),
+                Segment::Param {
+                    name: "version".to_string(),
+                    optional: false
+                },
+                Segment::Param {
+                    name: "page".to_string(),
+                    optional: true
+                },
+                Segment::CatchAll(Some("rest".to_string())),
+            ]
+        );
+
+        assert_eq!(
+            Segment::parse_route_definition("/*"),
+            vec![Segment::CatchAll(None)]
+        );
+    }
+
+    #[test]
+    fn specificity() {
+        let mut param_types = HashMap::new();
+        param_types.insert("id".to_string(), ParamType::U32);
+
+        let ranks = |route_definition| {
+            super::specificity(
+                &Segment::parse_route_definition(route_definition),
+                &param_types,
+            )
+        };
+
+        assert_eq!(
+            ranks("/users/:id/:name/:slug?/*"),
+            vec![
+                Rank::Static,
+                Rank::Typed,
+                Rank::Untyped,
+                Rank::Optional,
+                Rank::CatchAll
+            ]
+        );
+
+        assert!(ranks("/users/new") > ranks("/users/:id"));
+        assert!(ranks("/users/:id") > ranks("/users/:name"));
+        assert!(ranks("/users/:name") > ranks("/users/*"));
+    }
+
+    #[test]
+    fn catch_all_leaves_room_for_later_segments() {
+        let defined = Segment::parse_route_definition("/files/*path/edit");
+
+        let params = match_segments(&defined, &["files", "a", "b", "edit"], &HashMap::new());
+
+        assert_eq!(params.unwrap()["path"], "a/b");
+    }
+}
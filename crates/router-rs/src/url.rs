@@ -0,0 +1,291 @@
+//! Splitting URLs such as `/search?q=rust#results` into their path, query and fragment.
+
+use std::error::Error;
+use std::fmt;
+
+/// A URL's path along with its query string and fragment, such as `/search?q=rust#results`.
+///
+/// Path segments and query parameters are percent-decoded when parsing and percent-encoded
+/// when turning the `Url` back into a string.
+///
+/// ```rust,ignore
+/// let url = Url::parse("/search/rust%20lang?page=2#results");
+///
+/// assert_eq!(url.segments(), &["search", "rust lang"]);
+/// assert_eq!(url.query_param("page"), Some("2"));
+/// assert_eq!(url.fragment(), Some("results"));
+///
+/// let url = Url::new("/search").with_query("q", "rust lang");
+/// assert_eq!(url.to_string(), "/search?q=rust%20lang");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Url {
+    segments: Vec<String>,
+    query: Vec<(String, String)>,
+    fragment: Option<String>,
+}
+
+impl Url {
+    /// A URL with this path and no query or fragment. Each segment of `path` is used as is,
+    /// so it should not be percent-encoded.
+    pub fn new(path: &str) -> Url {
+        Url {
+            segments: split_path(path).map(|segment| segment.to_string()).collect(),
+            ..Url::default()
+        }
+    }
+
+    /// A URL with these (not yet percent-encoded) path segments
+    pub(crate) fn from_segments(segments: Vec<String>) -> Url {
+        Url {
+            segments,
+            ..Url::default()
+        }
+    }
+
+    /// Split a URL such as `/search?q=rust#results` into its path segments, query parameters
+    /// and fragment, percent-decoding each of them.
+    pub fn parse(url: &str) -> Url {
+        let (url, fragment) = match url.find('#') {
+            Some(idx) => (&url[..idx], Some(percent_decode(&url[idx + 1..]))),
+            None => (url, None),
+        };
+
+        let (path, query) = match url.find('?') {
+            Some(idx) => (&url[..idx], &url[idx + 1..]),
+            None => (url, ""),
+        };
+
+        let segments = split_path(path).map(percent_decode).collect();
+
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = match pair.find('=') {
+                    Some(idx) => (&pair[..idx], &pair[idx + 1..]),
+                    None => (pair, ""),
+                };
+
+                (decode_query(name), decode_query(value))
+            })
+            .collect();
+
+        Url {
+            segments,
+            query,
+            fragment,
+        }
+    }
+
+    /// The decoded segments of the path. `/users/5/` -> `["users", "5"]`
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// All of the decoded query parameters, in the order that they appear
+    pub fn query(&self) -> &[(String, String)] {
+        &self.query
+    }
+
+    /// The decoded value of the first query parameter with this name
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The decoded fragment, i.e. `results` for `/search#results`
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_ref().map(|fragment| fragment.as_str())
+    }
+
+    /// Add a query parameter to the end of the query string
+    pub fn with_query<V: ToString>(mut self, name: &str, value: V) -> Url {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the fragment
+    pub fn with_fragment(mut self, fragment: &str) -> Url {
+        self.fragment = Some(fragment.to_string());
+        self
+    }
+}
+
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/")?;
+
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| percent_encode(segment, PATH_SAFE))
+            .collect::<Vec<String>>();
+        write!(f, "{}", segments.join("/"))?;
+
+        for (idx, (name, value)) in self.query.iter().enumerate() {
+            write!(
+                f,
+                "{}{}={}",
+                if idx == 0 { "?" } else { "&" },
+                percent_encode(name, QUERY_SAFE),
+                percent_encode(value, QUERY_SAFE)
+            )?;
+        }
+
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", percent_encode(fragment, FRAGMENT_SAFE))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An error when creating a URL for a route with `Router::url_for`
+#[derive(Debug, Clone, PartialEq)]
+pub enum UrlError {
+    /// No route has this name
+    UnknownRoute { name: String },
+    /// The route's path has a parameter that wasn't provided
+    MissingParam { name: String },
+    /// The value can't be parsed into the parameter's `ParamType`, so the URL would not
+    /// match the route
+    InvalidParam { name: String, value: String },
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UrlError::UnknownRoute { name } => write!(f, "No route named {}", name),
+            UrlError::MissingParam { name } => write!(f, "Missing route parameter {}", name),
+            UrlError::InvalidParam { name, value } => {
+                write!(f, "Invalid value {:?} for route parameter {}", value, name)
+            }
+        }
+    }
+}
+
+impl Error for UrlError {}
+
+/// Characters other than letters, digits and `-._~` that don't need to be encoded in a path
+/// segment
+const PATH_SAFE: &str = "!$&'()*+,;=:@";
+/// ... in a query parameter's name or value. `&`, `=` and `+` mean something in a query.
+const QUERY_SAFE: &str = "!$'()*,;:@/?";
+/// ... in a fragment
+const FRAGMENT_SAFE: &str = "!$&'()*+,;=:@/?";
+
+/// "/users/5/" -> ["users", "5"]
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// `rust lang` -> `rust%20lang`
+fn percent_encode(text: &str, safe: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+
+    for byte in text.bytes() {
+        let character = byte as char;
+
+        if character.is_ascii_alphanumeric() || "-._~".contains(character) || safe.contains(character)
+        {
+            encoded.push(character);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+/// `rust%20lang` -> `rust lang`. Invalid escapes are left as they are.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = match (bytes[idx], bytes.get(idx + 1), bytes.get(idx + 2)) {
+            (b'%', Some(high), Some(low)) => hex_value(*high)
+                .and_then(|high| hex_value(*low).map(|low| high * 16 + low)),
+            _ => None,
+        };
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Forms encode spaces in query strings as `+`
+fn decode_query(text: &str) -> String {
+    percent_decode(&text.replace('+', " "))
+}
+
+fn hex_value(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|value| value as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let url = Url::parse("/search/rust%20lang/?q=hello+world&empty&tag=a%26b#results%201");
+
+        assert_eq!(url.segments(), &["search", "rust lang"]);
+        assert_eq!(
+            url.query(),
+            &[
+                ("q".to_string(), "hello world".to_string()),
+                ("empty".to_string(), "".to_string()),
+                ("tag".to_string(), "a&b".to_string()),
+            ]
+        );
+        assert_eq!(url.query_param("tag"), Some("a&b"));
+        assert_eq!(url.query_param("missing"), None);
+        assert_eq!(url.fragment(), Some("results 1"));
+    }
+
+    #[test]
+    fn parse_path_only() {
+        assert_eq!(Url::parse("/"), Url::default());
+        assert_eq!(Url::parse("/users/5"), Url::new("/users/5"));
+    }
+
+    #[test]
+    fn invalid_escapes_are_kept() {
+        assert_eq!(Url::parse("/100%/%zz").segments(), &["100%", "%zz"]);
+    }
+
+    #[test]
+    fn to_string() {
+        let url = Url::new("/search/rust lang")
+            .with_query("q", "a&b=c")
+            .with_query("page", 2)
+            .with_fragment("top");
+
+        assert_eq!(url.to_string(), "/search/rust%20lang?q=a%26b%3Dc&page=2#top");
+        assert_eq!(Url::parse(&url.to_string()), url);
+    }
+
+    #[test]
+    fn encodes_slashes_within_segments() {
+        let url = Url::parse("/files/a%2Fb");
+
+        assert_eq!(url.segments(), &["files", "a/b"]);
+        assert_eq!(url.to_string(), "/files/a%2Fb");
+    }
+}
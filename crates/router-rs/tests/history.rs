@@ -0,0 +1,244 @@
+//! Ensure that History keeps the browser's URL in sync with the app's path, in both of its
+//! modes.
+
+use js_sys::Promise;
+use router_rs::{History, HistoryMode};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+use web_sys::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// A History along with every path that it called `on_change` with
+struct TestHistory {
+    history: History,
+    paths: Rc<RefCell<Vec<String>>>,
+    /// The test runner's URL, which we go back to when we're done
+    original_url: String,
+}
+
+impl TestHistory {
+    fn new(mode: HistoryMode) -> TestHistory {
+        let original_url = window().location().href().unwrap();
+
+        let paths = Rc::new(RefCell::new(vec![]));
+        let paths_clone = Rc::clone(&paths);
+        let history = History::new(
+            mode,
+            Box::new(move |path: &str| paths_clone.borrow_mut().push(path.to_string())),
+        );
+
+        TestHistory {
+            history,
+            paths,
+            original_url,
+        }
+    }
+
+    fn paths(&self) -> Vec<String> {
+        self.paths.borrow().clone()
+    }
+}
+
+impl Drop for TestHistory {
+    fn drop(&mut self) {
+        browser_history()
+            .replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&self.original_url))
+            .unwrap();
+    }
+}
+
+#[wasm_bindgen_test]
+fn navigate_and_replace_path() {
+    let test = TestHistory::new(HistoryMode::Path);
+    let length = browser_history().length().unwrap();
+
+    test.history.navigate("/users/5?tab=posts");
+    assert_eq!(window().location().pathname().unwrap(), "/users/5");
+    assert_eq!(window().location().search().unwrap(), "?tab=posts");
+    assert_eq!(browser_history().length().unwrap(), length + 1);
+
+    // Redirects don't add another history entry
+    test.history.replace("/login");
+    assert_eq!(window().location().pathname().unwrap(), "/login");
+    assert_eq!(browser_history().length().unwrap(), length + 1);
+
+    assert_eq!(test.history.current_path(), "/login");
+    assert_eq!(test.paths(), vec!["/users/5?tab=posts", "/login"]);
+}
+
+#[wasm_bindgen_test]
+fn navigate_and_replace_hash() {
+    let test = TestHistory::new(HistoryMode::Hash);
+    let pathname = window().location().pathname().unwrap();
+    let length = browser_history().length().unwrap();
+
+    test.history.navigate("/users/5");
+    assert_eq!(window().location().hash().unwrap(), "#/users/5");
+    assert_eq!(browser_history().length().unwrap(), length + 1);
+
+    test.history.replace("/login");
+    assert_eq!(window().location().hash().unwrap(), "#/login");
+    assert_eq!(browser_history().length().unwrap(), length + 1);
+
+    // The real path is left alone
+    assert_eq!(window().location().pathname().unwrap(), pathname);
+
+    assert_eq!(test.history.current_path(), "/login");
+    assert_eq!(test.paths(), vec!["/users/5", "/login"]);
+}
+
+#[wasm_bindgen_test]
+fn popstate_path() {
+    let test = TestHistory::new(HistoryMode::Path);
+
+    // Pushing state doesn't fire popstate, so we pretend that the user pressed back
+    push_url("/contributors");
+    pop_state();
+
+    assert_eq!(test.paths(), vec!["/contributors"]);
+}
+
+#[wasm_bindgen_test]
+fn popstate_hash() {
+    let test = TestHistory::new(HistoryMode::Hash);
+
+    push_url("#/contributors");
+    pop_state();
+
+    // An empty hash is the root path
+    push_url("#");
+    pop_state();
+
+    assert_eq!(test.paths(), vec!["/contributors", "/"]);
+}
+
+#[wasm_bindgen_test]
+fn intercepts_link_clicks_path() {
+    let test = TestHistory::new(HistoryMode::Path);
+
+    let link = append_link("/contributors?page=2");
+    link.click();
+    link.remove();
+
+    assert_eq!(window().location().pathname().unwrap(), "/contributors");
+    assert_eq!(test.paths(), vec!["/contributors?page=2"]);
+}
+
+#[wasm_bindgen_test]
+fn intercepts_link_clicks_hash() {
+    let test = TestHistory::new(HistoryMode::Hash);
+
+    let link = append_link("/contributors");
+    link.click();
+    link.remove();
+
+    assert_eq!(window().location().hash().unwrap(), "#/contributors");
+    assert_eq!(test.paths(), vec!["/contributors"]);
+}
+
+#[wasm_bindgen_test]
+async fn restores_scroll_going_back_and_forward() {
+    let test = TestHistory::new(HistoryMode::Path);
+    let tall = append_tall_element();
+
+    test.history.navigate("/first");
+    next_frame().await;
+    window().scroll_to_with_x_and_y(0., 100.);
+
+    test.history.navigate("/second");
+    next_frame().await;
+    assert_eq!(window().scroll_y().unwrap(), 0.);
+    window().scroll_to_with_x_and_y(0., 200.);
+
+    browser_history().back().unwrap();
+    path_changed_to(&test, "/first").await;
+    assert_eq!(window().scroll_y().unwrap(), 100.);
+
+    // We left /first again without navigating, so its new position gets saved too
+    window().scroll_to_with_x_and_y(0., 150.);
+
+    browser_history().forward().unwrap();
+    path_changed_to(&test, "/second").await;
+    assert_eq!(window().scroll_y().unwrap(), 200.);
+
+    browser_history().back().unwrap();
+    path_changed_to(&test, "/first").await;
+    assert_eq!(window().scroll_y().unwrap(), 150.);
+
+    tall.remove();
+}
+
+#[wasm_bindgen_test]
+fn stops_listening_when_dropped() {
+    let test = TestHistory::new(HistoryMode::Path);
+    let paths = Rc::clone(&test.paths);
+    drop(test);
+
+    pop_state();
+
+    assert_eq!(*paths.borrow(), Vec::<String>::new());
+}
+
+fn window() -> Window {
+    web_sys::window().unwrap()
+}
+
+fn browser_history() -> web_sys::History {
+    window().history().unwrap()
+}
+
+fn push_url(url: &str) {
+    browser_history()
+        .push_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(url))
+        .unwrap();
+}
+
+fn pop_state() {
+    let event = PopStateEvent::new("popstate").unwrap();
+    window().dispatch_event(&event).unwrap();
+}
+
+/// Resolves on the next animation frame, which is when History scrolls
+async fn next_frame() {
+    let frame = Promise::new(&mut |resolve, _reject| {
+        window().request_animation_frame(&resolve).unwrap();
+    });
+
+    JsFuture::from(frame).await.unwrap();
+}
+
+/// Going back and forward fires popstate later on, so we wait until History has told us
+/// about the new path and then scrolled
+async fn path_changed_to(test: &TestHistory, path: &str) {
+    while test.paths().last().map(|last| last.as_str()) != Some(path) {
+        next_frame().await;
+    }
+    next_frame().await;
+}
+
+/// Makes the page tall enough to scroll
+fn append_tall_element() -> Element {
+    let document = window().document().unwrap();
+
+    let tall = document.create_element("div").unwrap();
+    tall.set_attribute("style", "height: 10000px").unwrap();
+    document.body().unwrap().append_child(&tall).unwrap();
+
+    tall
+}
+
+/// A same-origin link in the document, for clicking on
+fn append_link(href: &str) -> HtmlElement {
+    let document = window().document().unwrap();
+
+    let link = document.create_element("a").unwrap();
+    link.set_attribute("href", href).unwrap();
+    link.set_text_content(Some("Link"));
+    document.body().unwrap().append_child(&link).unwrap();
+
+    link.dyn_into::<HtmlElement>().unwrap()
+}
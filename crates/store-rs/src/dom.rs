@@ -0,0 +1,38 @@
+use crate::{State, Store, SubscriptionId};
+use virtual_dom_rs::{DomUpdater, VirtualNode};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+impl<S: State> Store<S> {
+    /// Render the data that the selector selects from the state and patch the DOM with the
+    /// result, whenever a message changes that data.
+    ///
+    /// `render` gets called while the store is dispatching, so it needs to render from the
+    /// data that it is given instead of borrowing the store. Messages that don't change the
+    /// selected data don't re-render.
+    ///
+    /// ```rust,ignore
+    /// let dom_updater = Rc::new(RefCell::new(DomUpdater::new_replace_mount(
+    ///     render(&store.todos),
+    ///     root_node,
+    /// )));
+    ///
+    /// store.render_into(dom_updater, |state: &State| state.todos.clone(), render);
+    /// ```
+    pub fn render_into<T, Sel, F>(
+        &mut self,
+        dom_updater: Rc<RefCell<DomUpdater>>,
+        selector: Sel,
+        render: F,
+    ) -> SubscriptionId
+    where
+        T: PartialEq + 'static,
+        Sel: Fn(&S) -> T + 'static,
+        F: Fn(&T) -> VirtualNode + 'static,
+    {
+        self.subscribe_to(selector, move |selected: &T| {
+            dom_updater.borrow_mut().update(render(selected))
+        })
+    }
+}
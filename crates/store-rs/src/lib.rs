@@ -0,0 +1,279 @@
+//! A store for your application's state.
+//!
+//! Your state only changes in response to messages. `Store::dispatch` runs a message through
+//! your state's `msg` function and any other reducers, then lets your middleware and
+//! subscriptions know about the new state.
+//!
+//! ```rust,ignore
+//! pub struct State {
+//!     click_count: u32,
+//!     todos: Vec<String>,
+//! }
+//!
+//! pub enum Msg {
+//!     Click,
+//!     AddTodo(String),
+//! }
+//!
+//! impl store_rs::State for State {
+//!     type Msg = Msg;
+//!
+//!     fn msg(&mut self, msg: &Msg) {
+//!         if let Msg::Click = msg {
+//!             self.click_count += 1;
+//!         }
+//!     }
+//! }
+//!
+//! let mut store = Store::new(State { click_count: 0, todos: vec![] });
+//!
+//! // Reducers for part of the state can live next to the views that use it
+//! store.add_reducer(reducer::slice(|state: &mut State| &mut state.todos, todos::reduce));
+//!
+//! // Only called when the click count changes, not for every message
+//! store.subscribe_to(
+//!     |state: &State| state.click_count,
+//!     |click_count: &u32| println!("Clicked {} times", click_count),
+//! );
+//!
+//! store.dispatch(Msg::Click);
+//! assert_eq!(store.click_count, 1);
+//! ```
+
+#![deny(missing_docs)]
+
+use std::ops::Deref;
+
+mod dom;
+
+mod middleware;
+pub use self::middleware::Middleware;
+
+pub mod reducer;
+use self::reducer::Reducer;
+
+mod subscription;
+use self::subscription::Subscription;
+pub use self::subscription::SubscriptionId;
+
+/// Your application's state
+pub trait State: 'static {
+    /// The messages that update the state
+    type Msg;
+
+    /// Update the state in response to a message. This is your root reducer, which can hand
+    /// the message off to functions that update parts of the state.
+    fn msg(&mut self, msg: &Self::Msg);
+}
+
+/// Holds your application's state and updates it in response to messages.
+///
+/// A `Store` dereferences to your state, so you can read it with `store.click_count()`.
+pub struct Store<S: State> {
+    state: S,
+    reducers: Vec<Reducer<S, S::Msg>>,
+    middleware: Vec<Box<Middleware<S>>>,
+    subscriptions: Vec<Subscription<S>>,
+    next_subscription_id: u32,
+}
+
+impl<S: State> Store<S> {
+    /// Create a store that holds your state
+    pub fn new(state: S) -> Store<S> {
+        Store {
+            state,
+            reducers: vec![],
+            middleware: vec![],
+            subscriptions: vec![],
+            next_subscription_id: 0,
+        }
+    }
+
+    /// Update the state in response to a message.
+    ///
+    /// The message goes through every middleware's `before_msg`, then `State::msg` and the
+    /// reducers in the order that they were added, then every middleware's `after_msg`.
+    /// Subscriptions get called last.
+    pub fn dispatch(&mut self, msg: S::Msg) {
+        for middleware in self.middleware.iter_mut() {
+            middleware.before_msg(&self.state, &msg);
+        }
+
+        self.state.msg(&msg);
+        for reducer in self.reducers.iter() {
+            reducer(&mut self.state, &msg);
+        }
+
+        for middleware in self.middleware.iter_mut() {
+            middleware.after_msg(&self.state, &msg);
+        }
+
+        for subscription in self.subscriptions.iter_mut() {
+            (subscription.listener)(&self.state);
+        }
+    }
+
+    /// Run every message through another reducer after `State::msg`. See `reducer::slice` for
+    /// reducers that update part of your state.
+    pub fn add_reducer(&mut self, reducer: Reducer<S, S::Msg>) {
+        self.reducers.push(reducer);
+    }
+
+    /// Add middleware, such as a logger or something that persists your state. Middleware
+    /// gets called in the order that it was added.
+    pub fn add_middleware(&mut self, middleware: Box<Middleware<S>>) {
+        self.middleware.push(middleware);
+    }
+
+    /// Call the listener with the state after every message
+    pub fn subscribe<F>(&mut self, mut listener: F) -> SubscriptionId
+    where
+        F: FnMut(&S) + 'static,
+    {
+        self.add_subscription(Box::new(move |state: &S| listener(state)))
+    }
+
+    /// Call the listener whenever a message changes the data that the selector selects from
+    /// the state, such as `|state: &State| state.click_count`.
+    ///
+    /// Messages that don't change the selected data don't call the listener.
+    pub fn subscribe_to<T, Sel, F>(&mut self, selector: Sel, mut listener: F) -> SubscriptionId
+    where
+        T: PartialEq + 'static,
+        Sel: Fn(&S) -> T + 'static,
+        F: FnMut(&T) + 'static,
+    {
+        let mut selected = selector(&self.state);
+
+        self.add_subscription(Box::new(move |state: &S| {
+            let new_selected = selector(state);
+
+            if new_selected != selected {
+                listener(&new_selected);
+                selected = new_selected;
+            }
+        }))
+    }
+
+    /// Stop calling a subscription's listener
+    pub fn unsubscribe(&mut self, id: SubscriptionId) {
+        self.subscriptions.retain(|subscription| subscription.id != id);
+    }
+
+    /// The state that the store holds
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    fn add_subscription(&mut self, listener: Box<FnMut(&S)>) -> SubscriptionId {
+        let id = SubscriptionId(self.next_subscription_id);
+        self.next_subscription_id += 1;
+
+        self.subscriptions.push(Subscription { id, listener });
+
+        id
+    }
+}
+
+impl<S: State> Deref for Store<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Counter {
+        count: u32,
+        name: String,
+    }
+
+    enum Msg {
+        Increment,
+        Rename(&'static str),
+    }
+
+    impl State for Counter {
+        type Msg = Msg;
+
+        fn msg(&mut self, msg: &Msg) {
+            match msg {
+                Msg::Increment => self.count += 1,
+                Msg::Rename(name) => self.name = name.to_string(),
+            }
+        }
+    }
+
+    fn store() -> Store<Counter> {
+        Store::new(Counter {
+            count: 0,
+            name: "counter".to_string(),
+        })
+    }
+
+    #[test]
+    fn dispatch_updates_state() {
+        let mut store = store();
+
+        store.dispatch(Msg::Increment);
+        store.dispatch(Msg::Increment);
+
+        assert_eq!(store.count, 2);
+    }
+
+    #[test]
+    fn subscriptions_get_every_message() {
+        let mut store = store();
+        let counts = Rc::new(RefCell::new(vec![]));
+
+        let counts_clone = Rc::clone(&counts);
+        let id =
+            store.subscribe(move |state: &Counter| counts_clone.borrow_mut().push(state.count));
+
+        store.dispatch(Msg::Increment);
+        store.dispatch(Msg::Rename("renamed"));
+        store.unsubscribe(id);
+        store.dispatch(Msg::Increment);
+
+        assert_eq!(*counts.borrow(), vec![1, 1]);
+    }
+
+    #[test]
+    fn selector_subscriptions_only_fire_on_changes() {
+        let mut store = store();
+        let names = Rc::new(RefCell::new(vec![]));
+
+        let names_clone = Rc::clone(&names);
+        store.subscribe_to(
+            |state: &Counter| state.name.clone(),
+            move |name: &String| names_clone.borrow_mut().push(name.clone()),
+        );
+
+        store.dispatch(Msg::Increment);
+        store.dispatch(Msg::Rename("renamed"));
+        store.dispatch(Msg::Rename("renamed"));
+        store.dispatch(Msg::Increment);
+
+        assert_eq!(*names.borrow(), vec!["renamed".to_string()]);
+    }
+
+    #[test]
+    fn reducers_run_after_state_msg() {
+        let mut store = store();
+
+        store.add_reducer(Box::new(|state: &mut Counter, msg: &Msg| {
+            if let Msg::Increment = msg {
+                state.name = format!("counter {}", state.count);
+            }
+        }));
+        store.dispatch(Msg::Increment);
+
+        assert_eq!(store.name, "counter 1");
+    }
+}
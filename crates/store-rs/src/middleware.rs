@@ -0,0 +1,67 @@
+use crate::State;
+
+/// Hooks into every message that the store dispatches, such as for logging messages or
+/// persisting the state.
+///
+/// ```rust,ignore
+/// struct Logger;
+///
+/// impl Middleware<State> for Logger {
+///     fn before_msg(&mut self, _state: &State, msg: &Msg) {
+///         println!("Dispatching {:?}", msg);
+///     }
+/// }
+///
+/// store.add_middleware(Box::new(Logger));
+/// ```
+pub trait Middleware<S: State> {
+    /// Called before the state gets updated
+    fn before_msg(&mut self, _state: &S, _msg: &S::Msg) {}
+
+    /// Called after the state was updated, before subscriptions get called
+    fn after_msg(&mut self, _state: &S, _msg: &S::Msg) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Store;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Clicks(u32);
+
+    impl State for Clicks {
+        type Msg = ();
+
+        fn msg(&mut self, _msg: &()) {
+            self.0 += 1;
+        }
+    }
+
+    /// Saves every (before, after) click count
+    struct Persist(Rc<RefCell<Vec<(u32, u32)>>>);
+
+    impl Middleware<Clicks> for Persist {
+        fn before_msg(&mut self, state: &Clicks, _msg: &()) {
+            self.0.borrow_mut().push((state.0, 0));
+        }
+
+        fn after_msg(&mut self, state: &Clicks, _msg: &()) {
+            self.0.borrow_mut().last_mut().unwrap().1 = state.0;
+        }
+    }
+
+    #[test]
+    fn middleware_sees_state_before_and_after() {
+        let saved = Rc::new(RefCell::new(vec![]));
+
+        let mut store = Store::new(Clicks(0));
+        store.add_middleware(Box::new(Persist(Rc::clone(&saved))));
+
+        store.dispatch(());
+        store.dispatch(());
+
+        assert_eq!(*saved.borrow(), vec![(0, 1), (1, 2)]);
+    }
+}
@@ -0,0 +1,74 @@
+//! Reducers update your state in response to a message.
+//!
+//! `State::msg` is your root reducer. Reducers that only know about part of your state can be
+//! composed into it with `slice` and `Store::add_reducer`, or with `combine`.
+
+/// Updates the state `S` in response to a message `M`
+pub type Reducer<S, M> = Box<Fn(&mut S, &M)>;
+
+/// A reducer for the part of the state that `select` returns.
+///
+/// ```rust,ignore
+/// // todos::reduce is a fn(&mut Vec<Todo>, &Msg)
+/// store.add_reducer(reducer::slice(|state: &mut State| &mut state.todos, todos::reduce));
+/// ```
+pub fn slice<S, T, M, R>(select: fn(&mut S) -> &mut T, reducer: R) -> Reducer<S, M>
+where
+    S: 'static,
+    T: 'static,
+    M: 'static,
+    R: Fn(&mut T, &M) + 'static,
+{
+    Box::new(move |state: &mut S, msg: &M| reducer(select(state), msg))
+}
+
+/// One reducer that runs every one of these reducers in order
+pub fn combine<S: 'static, M: 'static>(reducers: Vec<Reducer<S, M>>) -> Reducer<S, M> {
+    Box::new(move |state: &mut S, msg: &M| {
+        for reducer in reducers.iter() {
+            reducer(state, msg);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct State {
+        count: u32,
+        todos: Vec<&'static str>,
+    }
+
+    enum Msg {
+        Add(&'static str),
+    }
+
+    fn count(count: &mut u32, _msg: &Msg) {
+        *count += 1;
+    }
+
+    fn todos(todos: &mut Vec<&'static str>, msg: &Msg) {
+        match msg {
+            Msg::Add(todo) => todos.push(todo),
+        }
+    }
+
+    #[test]
+    fn combine_slice_reducers() {
+        let reducer = combine(vec![
+            slice(|state: &mut State| &mut state.count, count),
+            slice(|state: &mut State| &mut state.todos, todos),
+        ]);
+
+        let mut state = State {
+            count: 0,
+            todos: vec![],
+        };
+        reducer(&mut state, &Msg::Add("Write docs"));
+        reducer(&mut state, &Msg::Add("Ship it"));
+
+        assert_eq!(state.count, 2);
+        assert_eq!(state.todos, vec!["Write docs", "Ship it"]);
+    }
+}
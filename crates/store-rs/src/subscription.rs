@@ -0,0 +1,9 @@
+/// Identifies a subscription so that it can be removed with `Store::unsubscribe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(pub(crate) u32);
+
+pub(crate) struct Subscription<S> {
+    pub(crate) id: SubscriptionId,
+    /// Gets called with the state after every message
+    pub(crate) listener: Box<FnMut(&S)>,
+}
@@ -0,0 +1,104 @@
+//! Ensure that a store patches the DOM when the state that it renders changes, and only then.
+
+#![feature(proc_macro_hygiene)]
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+use store_rs::Store;
+use virtual_dom_rs::prelude::*;
+use virtual_dom_rs::web_sys;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+struct Counter {
+    count: u32,
+    name: String,
+}
+
+enum Msg {
+    Increment,
+    Rename(&'static str),
+}
+
+impl store_rs::State for Counter {
+    type Msg = Msg;
+
+    fn msg(&mut self, msg: &Msg) {
+        match msg {
+            Msg::Increment => self.count += 1,
+            Msg::Rename(name) => self.name = name.to_string(),
+        }
+    }
+}
+
+fn render(count: &u32) -> VirtualNode {
+    let count = &count.to_string();
+
+    html! { <div id="store-count">{ text!(count) }</div> }
+}
+
+/// A store that renders its count into the document, along with how many times it rendered
+fn render_count() -> (Store<Counter>, Rc<Cell<u32>>) {
+    let body = web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .body()
+        .unwrap();
+
+    let mut store = Store::new(Counter {
+        count: 0,
+        name: "counter".to_string(),
+    });
+
+    let dom_updater = Rc::new(RefCell::new(DomUpdater::new_append_to_mount(
+        render(&store.count),
+        &body,
+    )));
+
+    let renders = Rc::new(Cell::new(0));
+    let renders_clone = Rc::clone(&renders);
+    store.render_into(
+        dom_updater,
+        |state: &Counter| state.count,
+        move |count: &u32| {
+            renders_clone.set(renders_clone.get() + 1);
+            render(count)
+        },
+    );
+
+    (store, renders)
+}
+
+fn rendered_count() -> String {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let element = document.get_element_by_id("store-count").unwrap();
+    let text = element.text_content().unwrap();
+
+    element.remove();
+    text
+}
+
+#[wasm_bindgen_test]
+fn dispatching_patches_the_dom() {
+    let (mut store, renders) = render_count();
+
+    store.dispatch(Msg::Increment);
+    store.dispatch(Msg::Increment);
+
+    assert_eq!(renders.get(), 2);
+    assert_eq!(rendered_count(), "2");
+}
+
+#[wasm_bindgen_test]
+fn unrelated_changes_do_not_render() {
+    let (mut store, renders) = render_count();
+
+    store.dispatch(Msg::Rename("renamed"));
+
+    assert_eq!(store.name, "renamed");
+    assert_eq!(renders.get(), 0);
+    assert_eq!(rendered_count(), "0");
+}
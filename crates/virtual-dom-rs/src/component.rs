@@ -0,0 +1,416 @@
+//! Components with their own state and lifecycle
+
+use crate::{DomUpdater, View, VirtualNode};
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Node};
+
+/// A view with its own state, which changes in response to messages.
+///
+/// A mounted component re-renders itself after a message changes its state, and only its own
+/// part of the DOM gets diffed and patched. So state that only matters to one part of the page
+/// doesn't have to live in your application's store.
+///
+/// ```rust,ignore
+/// struct Counter {
+///     count: u32,
+/// }
+///
+/// enum Msg {
+///     Increment,
+/// }
+///
+/// impl Component for Counter {
+///     type Msg = Msg;
+///
+///     fn update(&mut self, msg: Msg) -> bool {
+///         match msg {
+///             Msg::Increment => self.count += 1,
+///         };
+///
+///         true
+///     }
+///
+///     fn view(&self, handle: &ComponentHandle<Self>) -> VirtualNode {
+///         let handle = handle.clone();
+///         let count = &self.count.to_string();
+///
+///         html! {
+///           <button onclick=move |_: u8| handle.send(Msg::Increment)>
+///             { text!(count) }
+///           </button>
+///         }
+///     }
+///
+///     fn mounted(&mut self, element: &Element) {
+///         element.set_attribute("data-mounted", "true").unwrap();
+///     }
+/// }
+///
+/// let counter = ComponentHandle::new(Counter { count: 0 });
+/// counter.append_to(&document.body().unwrap());
+/// ```
+///
+/// Every `View` is also a `Component` without any messages or lifecycle hooks.
+pub trait Component: Sized + 'static {
+    /// The messages that update the component's state
+    type Msg: 'static;
+
+    /// Update the component's state in response to a message. Return whether the component
+    /// should re-render.
+    fn update(&mut self, msg: Self::Msg) -> bool;
+
+    /// Render the component. Event handlers can use a clone of the handle to send the
+    /// component messages.
+    fn view(&self, handle: &ComponentHandle<Self>) -> VirtualNode;
+
+    /// Called after the component's root element was added to the DOM
+    fn mounted(&mut self, _element: &Element) {}
+
+    /// Called after the component re-rendered and its root element was patched
+    fn updated(&mut self, _element: &Element) {}
+
+    /// Called right before the component's root element gets removed from the DOM
+    fn before_unmount(&mut self, _element: &Element) {}
+}
+
+impl<V: View + 'static> Component for V {
+    type Msg = ();
+
+    /// Sending a `View` a message re-renders it
+    fn update(&mut self, _msg: ()) -> bool {
+        true
+    }
+
+    fn view(&self, _handle: &ComponentHandle<Self>) -> VirtualNode {
+        View::render(self)
+    }
+}
+
+/// Owns a component's state and keeps its part of the DOM up to date.
+///
+/// Cloning a handle is cheap, and every clone refers to the same component.
+///
+/// Handles that are captured by the component's own event handlers keep it alive until it
+/// gets unmounted, so unmount components that you're done with.
+pub struct ComponentHandle<C: Component> {
+    shared: Rc<Shared<C>>,
+}
+
+struct Shared<C: Component> {
+    component: RefCell<C>,
+    /// Patches the component's part of the DOM, once it is mounted
+    dom_updater: RefCell<Option<DomUpdater>>,
+    /// Messages that were sent while we were busy updating or rendering
+    queue: RefCell<VecDeque<C::Msg>>,
+    busy: Cell<bool>,
+}
+
+impl<C: Component> ComponentHandle<C> {
+    /// Create a handle for a component that isn't mounted yet
+    pub fn new(component: C) -> ComponentHandle<C> {
+        ComponentHandle {
+            shared: Rc::new(Shared {
+                component: RefCell::new(component),
+                dom_updater: RefCell::new(None),
+                queue: RefCell::new(VecDeque::new()),
+                busy: Cell::new(false),
+            }),
+        }
+    }
+
+    /// Update the component's state, then re-render it if it is mounted and `update` says
+    /// that it should.
+    ///
+    /// Messages that are sent while the component is updating, rendering or in a lifecycle
+    /// hook get handled right after it is done.
+    pub fn send(&self, msg: C::Msg) {
+        self.shared.queue.borrow_mut().push_back(msg);
+        self.process_queue();
+    }
+
+    /// Render the component into a new element that gets appended to `parent`.
+    ///
+    /// The component patches its own part of the DOM, so `parent` shouldn't also be patched
+    /// by a parent `DomUpdater`. Mount it into an element that you render without children.
+    ///
+    /// Components can't be embedded in another view's `html!`, so this or `replace` is how a
+    /// component gets into a page.
+    pub fn append_to(&self, parent: &Element) {
+        let dom_updater = DomUpdater::new_append_to_mount(self.render(), parent);
+        self.mount(dom_updater);
+    }
+
+    /// Render the component in place of `mount`, such as the server rendered version of the
+    /// component.
+    pub fn replace(&self, mount: Element) {
+        let dom_updater = DomUpdater::new_replace_mount(self.render(), mount);
+        self.mount(dom_updater);
+    }
+
+    /// Remove the component from the DOM. It can be mounted again later, with the same state.
+    pub fn unmount(&self) {
+        let dom_updater = match self.shared.dom_updater.borrow_mut().take() {
+            Some(dom_updater) => dom_updater,
+            None => return,
+        };
+
+        let root_node = dom_updater.root_node();
+        self.hook(&root_node, |component, element| {
+            component.before_unmount(element)
+        });
+
+        if let Some(parent) = root_node.parent_node() {
+            let _ = parent.remove_child(&root_node);
+        }
+
+        // Dropping the DomUpdater drops the event handlers, along with the handles that they
+        // captured
+        drop(dom_updater);
+        self.process_queue();
+    }
+
+    /// Whether the component is in the DOM
+    pub fn is_mounted(&self) -> bool {
+        self.shared.dom_updater.borrow().is_some()
+    }
+
+    /// Render the component's current state, such as for rendering it on the server
+    pub fn render(&self) -> VirtualNode {
+        self.shared.component.borrow().view(self)
+    }
+
+    /// The component, for reading its state
+    pub fn component(&self) -> Ref<'_, C> {
+        self.shared.component.borrow()
+    }
+
+    fn mount(&self, dom_updater: DomUpdater) {
+        let root_node = dom_updater.root_node();
+        *self.shared.dom_updater.borrow_mut() = Some(dom_updater);
+
+        self.hook(&root_node, |component, element| component.mounted(element));
+        self.process_queue();
+    }
+
+    fn process_queue(&self) {
+        if self.shared.busy.get() {
+            return;
+        }
+        self.shared.busy.set(true);
+
+        loop {
+            let mut should_render = false;
+
+            loop {
+                let msg = match self.shared.queue.borrow_mut().pop_front() {
+                    Some(msg) => msg,
+                    None => break,
+                };
+
+                should_render = self.shared.component.borrow_mut().update(msg) || should_render;
+            }
+
+            // However many messages we handled we only need to render once
+            if !should_render || !self.patch() {
+                break;
+            }
+        }
+
+        self.shared.busy.set(false);
+    }
+
+    /// Re-render if we're mounted, returning whether we did
+    fn patch(&self) -> bool {
+        if !self.is_mounted() {
+            return false;
+        }
+
+        let vdom = self.render();
+
+        let root_node = {
+            let mut dom_updater = self.shared.dom_updater.borrow_mut();
+            let dom_updater = dom_updater.as_mut().expect("Mounted");
+
+            dom_updater.update(vdom);
+            dom_updater.root_node()
+        };
+
+        self.call_hook(&root_node, |component, element| component.updated(element));
+
+        true
+    }
+
+    /// Call a lifecycle hook with the component's root element, handling any messages that
+    /// it sends once it's done
+    fn hook<F>(&self, root_node: &Node, hook: F)
+    where
+        F: FnOnce(&mut C, &Element),
+    {
+        let was_busy = self.shared.busy.replace(true);
+        self.call_hook(root_node, hook);
+        self.shared.busy.set(was_busy);
+    }
+
+    fn call_hook<F>(&self, root_node: &Node, hook: F)
+    where
+        F: FnOnce(&mut C, &Element),
+    {
+        if let Some(element) = root_node.dyn_ref::<Element>() {
+            hook(&mut *self.shared.component.borrow_mut(), element);
+        }
+    }
+}
+
+impl<C: Component> Clone for ComponentHandle<C> {
+    fn clone(&self) -> Self {
+        ComponentHandle {
+            shared: Rc::clone(&self.shared),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    struct Counter {
+        count: u32,
+    }
+
+    enum Msg {
+        Increment,
+        IncrementTwice,
+        Nothing,
+    }
+
+    impl Component for Counter {
+        type Msg = Msg;
+
+        fn update(&mut self, msg: Msg) -> bool {
+            match msg {
+                Msg::Increment => self.count += 1,
+                Msg::IncrementTwice => self.count += 2,
+                Msg::Nothing => return false,
+            };
+
+            true
+        }
+
+        fn view(&self, handle: &ComponentHandle<Self>) -> VirtualNode {
+            let handle = handle.clone();
+            let count = &self.count.to_string();
+
+            html! { <button onclick=move |_: u8| handle.send(Msg::Increment)>{ text!(count) }</button> }
+        }
+    }
+
+    #[test]
+    fn messages_update_state() {
+        let counter = ComponentHandle::new(Counter { count: 0 });
+
+        counter.send(Msg::Increment);
+        counter.clone().send(Msg::IncrementTwice);
+        counter.send(Msg::Nothing);
+
+        assert_eq!(counter.component().count, 3);
+        assert_eq!(counter.render().to_string(), "<button>3</button>");
+        assert!(!counter.is_mounted());
+    }
+
+    /// Sends itself messages from inside of `update`
+    struct Echo {
+        handle: Rc<RefCell<Option<ComponentHandle<Echo>>>>,
+        log: Vec<String>,
+    }
+
+    enum EchoMsg {
+        Say(&'static str),
+        Echo(&'static str, u32),
+    }
+
+    impl Component for Echo {
+        type Msg = EchoMsg;
+
+        fn update(&mut self, msg: EchoMsg) -> bool {
+            let handle = self.handle.borrow().clone().unwrap();
+
+            match msg {
+                EchoMsg::Say(word) => {
+                    self.log.push(format!("say {}", word));
+                    handle.send(EchoMsg::Echo(word, 2));
+                }
+                EchoMsg::Echo(word, times) => {
+                    self.log.push(format!("echo {} {}", word, times));
+                    if times > 1 {
+                        handle.send(EchoMsg::Echo(word, times - 1));
+                    }
+                }
+            };
+            // Messages that we sent are handled after we return, not in the middle of us
+            self.log.push("done".to_string());
+
+            false
+        }
+
+        fn view(&self, _handle: &ComponentHandle<Self>) -> VirtualNode {
+            html! { <div></div> }
+        }
+    }
+
+    fn echo() -> ComponentHandle<Echo> {
+        let handle = Rc::new(RefCell::new(None));
+        let echo = ComponentHandle::new(Echo {
+            handle: Rc::clone(&handle),
+            log: vec![],
+        });
+        *handle.borrow_mut() = Some(echo.clone());
+
+        echo
+    }
+
+    #[test]
+    fn messages_sent_while_updating_are_queued() {
+        let echo = echo();
+
+        echo.send(EchoMsg::Say("hi"));
+
+        assert_eq!(
+            echo.component().log,
+            vec!["say hi", "done", "echo hi 2", "done", "echo hi 1", "done"]
+        );
+    }
+
+    #[test]
+    fn sending_after_the_queue_empties() {
+        let echo = echo();
+
+        echo.send(EchoMsg::Echo("a", 1));
+        echo.send(EchoMsg::Say("b"));
+
+        assert_eq!(
+            echo.component().log,
+            vec!["echo a 1", "done", "say b", "done", "echo b 2", "done", "echo b 1", "done"]
+        );
+    }
+
+    struct Greeting;
+
+    impl View for Greeting {
+        fn render(&self) -> VirtualNode {
+            html! { <div>Hello</div> }
+        }
+    }
+
+    #[test]
+    fn views_are_components() {
+        let greeting = ComponentHandle::new(Greeting);
+
+        greeting.send(());
+
+        assert_eq!(greeting.render().to_string(), "<div>Hello</div>");
+    }
+}
@@ -96,6 +96,15 @@ fn diff_recursive<'a, 'b>(
                 patches.push(Patch::RemoveAttributes(*cur_node_idx, remove_attributes));
             }
 
+            // Closures can't be compared, and the new ones might capture different values
+            if !old_element.events.0.is_empty() || !new_element.events.0.is_empty() {
+                patches.push(Patch::ReplaceEvents(
+                    *cur_node_idx,
+                    &old_element.events,
+                    &new_element.events,
+                ));
+            }
+
             let old_child_count = old_element.children.len();
             let new_child_count = new_element.children.len();
 
@@ -190,6 +199,34 @@ mod tests {
         .test();
     }
 
+    #[test]
+    fn replace_events() {
+        // Events are always equal to each other, since closures can't be compared
+        let events = crate::Events(HashMap::new());
+
+        DiffTestCase {
+            old: html! { <div> <button onclick=|_: u8| {}></button> </div> },
+            new: html! { <div> <button onclick=|_: u8| {}></button> </div> },
+            expected: vec![Patch::ReplaceEvents(1, &events, &events)],
+            description: "Replace the events of an element whose handlers might have changed",
+        }
+        .test();
+        DiffTestCase {
+            old: html! { <div onclick=|_: u8| {}></div> },
+            new: html! { <div></div> },
+            expected: vec![Patch::ReplaceEvents(0, &events, &events)],
+            description: "Stop listening to events that were removed",
+        }
+        .test();
+        DiffTestCase {
+            old: html! { <div> <span></span> </div> },
+            new: html! { <div> <span></span> </div> },
+            expected: vec![],
+            description: "Elements without events don't need their events replaced",
+        }
+        .test();
+    }
+
     #[test]
     fn remove_nodes() {
         DiffTestCase {
@@ -2,7 +2,6 @@
 
 use crate::diff::diff;
 use crate::patch::patch;
-use crate::patch::Patch;
 use std::collections::HashMap;
 use virtual_node::DynClosure;
 use virtual_node::VirtualNode;
@@ -73,7 +72,8 @@ impl DomUpdater {
     pub fn update(&mut self, new_vdom: VirtualNode) {
         let patches = diff(&self.current_vdom, &new_vdom);
 
-        patch(self.root_node.clone(), &patches);
+        let closures = patch(self.root_node.clone(), &patches);
+        self.active_closures.extend(closures);
 
         self.current_vdom = new_vdom;
     }
@@ -86,11 +86,3 @@ impl DomUpdater {
         self.root_node.clone()
     }
 }
-
-impl DomUpdater {
-    // FIXME: Implement this... Get the active_closures from our patches and merge them
-    // into our active closures.
-    fn update_active_closures(&mut self, patches: &Vec<Patch>) {
-        for _patch in patches.iter() {}
-    }
-}
@@ -29,18 +29,27 @@ pub use crate::patch::*;
 mod view;
 pub use crate::view::*;
 
+mod component;
+pub use crate::component::{Component, ComponentHandle};
+
 pub use html_macro::html;
 pub use html_macro::text;
 
 mod dom_updater;
 pub use self::dom_updater::DomUpdater;
 
+mod render_scheduler;
+pub use self::render_scheduler::RenderScheduler;
+
 /// Exports structs and macros that you'll almost always want access to in a virtual-dom
 /// powered application
 pub mod prelude {
+    pub use crate::component::{Component, ComponentHandle};
     pub use crate::dom_updater::DomUpdater;
+    pub use crate::render_scheduler::RenderScheduler;
     pub use crate::view::View;
     pub use crate::VirtualNode;
     pub use html_macro::html;
     pub use html_macro::text;
+    pub use virtual_node::assert_snapshot;
 }
@@ -6,12 +6,16 @@ use std::collections::HashSet;
 use wasm_bindgen::JsCast;
 use web_sys::{Element, Node, Text};
 
-use crate::{VirtualNode, VText};
+use crate::{DynClosure, VirtualNode, VText};
 
 /// Apply all of the patches to our old root node in order to create the new root node
 /// that we desire.
 /// This is usually used after diffing two virtual nodes.
-pub fn patch<N: Into<Node>>(root_node: N, patches: &Vec<Patch>) {
+///
+/// Returns the closures of the events that the patches started listening to, by the
+/// `data-vdom-id` of their element. Keep them alive for as long as the elements are in the
+/// page, or else their events will stop working.
+pub fn patch<N: Into<Node>>(root_node: N, patches: &Vec<Patch>) -> HashMap<u32, Vec<DynClosure>> {
     let root_node: Node = root_node.into();
 
     let mut cur_node_idx = 0;
@@ -33,11 +37,13 @@ pub fn patch<N: Into<Node>>(root_node: N, patches: &Vec<Patch>) {
         &mut text_nodes_to_patch,
     );
 
+    let mut closures = HashMap::new();
+
     for patch in patches {
         let patch_node_idx = patch.node_idx();
 
         if let Some(element) = element_nodes_to_patch.get(&patch_node_idx) {
-            apply_element_patch(&element, &patch);
+            apply_element_patch(&element, &patch, &mut closures);
             continue;
         }
 
@@ -48,6 +54,8 @@ pub fn patch<N: Into<Node>>(root_node: N, patches: &Vec<Patch>) {
 
         unreachable!("Getting here means we didn't find the element or next node that we were supposed to patch.")
     }
+
+    closures
 }
 
 
@@ -110,7 +118,7 @@ fn find_nodes(
     }
 }
 
-fn apply_element_patch(node: &Element, patch: &Patch) {
+fn apply_element_patch(node: &Element, patch: &Patch, closures: &mut HashMap<u32, Vec<DynClosure>>) {
     match patch {
         Patch::AddAttributes(_node_idx, attributes) => {
             for (attrib_name, attrib_val) in attributes.iter() {
@@ -125,8 +133,12 @@ fn apply_element_patch(node: &Element, patch: &Patch) {
             }
         }
         Patch::Replace(_node_idx, new_node) => {
-            node.replace_with_with_node_1(&new_node.create_dom_node().node)
+            let created_node = new_node.create_dom_node();
+
+            node.replace_with_with_node_1(&created_node.node)
                 .expect("Replacing node failed");
+
+            closures.extend(created_node.closures);
         }
         Patch::TruncateChildren(_node_idx, num_children_remaining) => {
             let children = node.child_nodes();
@@ -162,10 +174,21 @@ fn apply_element_patch(node: &Element, patch: &Patch) {
             let parent = &node;
 
             for new_node in new_nodes {
-                parent.append_child(&new_node.create_dom_node().node)
+                let created_node = new_node.create_dom_node();
+
+                parent.append_child(&created_node.node)
                     .expect("Appending child node failed");
+
+                closures.extend(created_node.closures);
             }
         }
+        Patch::ReplaceEvents(_node_idx, old_events, new_events) => {
+            old_events.detach(node);
+
+            // Replaces the old closures, which we just stopped listening to
+            let (unique_id, new_closures) = new_events.attach(node);
+            closures.insert(unique_id, new_closures);
+        }
         Patch::ChangeText(_node_idx, _new_node) => unreachable!(
             "Elements should not receive ChangeText patches. Those should go to Node's"
         ),
@@ -1,7 +1,7 @@
 //! Our Patch enum is intentionally kept in it's own file for easy inclusion into
 //! The Percy Book.
 
-use crate::{VirtualNode, VText};
+use crate::{Events, VirtualNode, VText};
 use std::collections::HashMap;
 
 mod apply_patches;
@@ -55,6 +55,9 @@ pub enum Patch<'a> {
     RemoveAttributes(NodeIdx, Vec<&'a str>),
     /// Change the text of a Text node.
     ChangeText(NodeIdx, &'a VText),
+    /// Stop listening to the old node's events and start listening to the new node's.
+    /// Closures can't be compared, so this happens whenever either node has events.
+    ReplaceEvents(NodeIdx, &'a Events, &'a Events),
 }
 
 type NodeIdx = usize;
@@ -71,6 +74,7 @@ impl<'a> Patch<'a> {
             Patch::AddAttributes(node_idx, _) => *node_idx,
             Patch::RemoveAttributes(node_idx, _) => *node_idx,
             Patch::ChangeText(node_idx, _) => *node_idx,
+            Patch::ReplaceEvents(node_idx, _, _) => *node_idx,
         }
     }
 }
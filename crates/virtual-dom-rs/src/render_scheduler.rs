@@ -0,0 +1,159 @@
+//! Re-render at most once per animation frame
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::Closure;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsCast;
+
+/// Coalesces requests to re-render your application into one render per animation frame.
+///
+/// Call `schedule` whenever your state changes, such as from a store subscription. However many
+/// times it gets called, your render function runs once, on the next animation frame.
+///
+/// ```rust,ignore
+/// let scheduler = RenderScheduler::new(move || dom_updater.update(app.render()));
+///
+/// let render = scheduler.clone();
+/// store.subscribe(move |_: &State| render.schedule());
+/// ```
+///
+/// Outside of the browser there are no animation frames, so nothing renders until you call
+/// `flush`.
+#[derive(Clone)]
+pub struct RenderScheduler {
+    shared: Rc<Shared>,
+}
+
+struct Shared {
+    /// Whether something changed since the last render
+    dirty: Cell<bool>,
+    /// Whether we're waiting on an animation frame
+    frame_requested: Cell<bool>,
+    render: RefCell<Box<FnMut()>>,
+}
+
+impl RenderScheduler {
+    /// Create a scheduler that calls `render` to re-render your application
+    pub fn new<F>(render: F) -> RenderScheduler
+    where
+        F: FnMut() + 'static,
+    {
+        RenderScheduler {
+            shared: Rc::new(Shared {
+                dirty: Cell::new(false),
+                frame_requested: Cell::new(false),
+                render: RefCell::new(Box::new(render)),
+            }),
+        }
+    }
+
+    /// Render on the next animation frame, unless a render is already scheduled for it
+    pub fn schedule(&self) {
+        self.shared.dirty.set(true);
+
+        if !self.shared.frame_requested.get() {
+            self.shared.frame_requested.set(true);
+            self.request_frame();
+        }
+    }
+
+    /// Render right away if a render was scheduled, instead of waiting for the next animation
+    /// frame. Useful in tests.
+    ///
+    /// Render functions that schedule another render, such as by following a redirect, get
+    /// rendered again on the next frame instead of recursively.
+    pub fn flush(&self) {
+        if !self.shared.dirty.get() {
+            return;
+        }
+
+        // We're already rendering, so whatever scheduled us will be rendered next time
+        let mut render = match self.shared.render.try_borrow_mut() {
+            Ok(render) => render,
+            Err(_) => return,
+        };
+
+        self.shared.dirty.set(false);
+        render();
+    }
+
+    /// Whether a render is scheduled
+    pub fn is_dirty(&self) -> bool {
+        self.shared.dirty.get()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn request_frame(&self) {
+        let scheduler = self.clone();
+        let on_frame = Closure::once_into_js(move || {
+            scheduler.shared.frame_requested.set(false);
+            scheduler.flush();
+        });
+
+        let _ = web_sys::window()
+            .expect("No window")
+            .request_animation_frame(on_frame.unchecked_ref());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn request_frame(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_once_per_flush() {
+        let renders = Rc::new(Cell::new(0));
+
+        let renders_clone = Rc::clone(&renders);
+        let scheduler = RenderScheduler::new(move || renders_clone.set(renders_clone.get() + 1));
+
+        scheduler.flush();
+        assert_eq!(renders.get(), 0);
+
+        scheduler.schedule();
+        scheduler.schedule();
+        scheduler.schedule();
+        assert!(scheduler.is_dirty());
+        assert_eq!(renders.get(), 0);
+
+        scheduler.flush();
+        scheduler.flush();
+        assert!(!scheduler.is_dirty());
+        assert_eq!(renders.get(), 1);
+    }
+
+    #[test]
+    fn scheduling_while_rendering() {
+        let scheduler = Rc::new(RefCell::new(None::<RenderScheduler>));
+        let renders = Rc::new(Cell::new(0));
+
+        let scheduler_clone = Rc::clone(&scheduler);
+        let renders_clone = Rc::clone(&renders);
+        *scheduler.borrow_mut() = Some(RenderScheduler::new(move || {
+            renders_clone.set(renders_clone.get() + 1);
+
+            // A redirect on the first render
+            if renders_clone.get() == 1 {
+                let scheduler = scheduler_clone.borrow();
+                let scheduler = scheduler.as_ref().unwrap();
+                scheduler.schedule();
+                scheduler.flush();
+            }
+        }));
+
+        let scheduler = scheduler.borrow().clone().unwrap();
+        scheduler.schedule();
+        scheduler.flush();
+        assert_eq!(renders.get(), 1);
+        assert!(scheduler.is_dirty());
+
+        scheduler.flush();
+        assert_eq!(renders.get(), 2);
+    }
+}
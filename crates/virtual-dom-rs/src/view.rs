@@ -2,6 +2,9 @@ use crate::VirtualNode;
 
 /// A trait with common functionality for rendering front-end views.
 ///
+/// A `View` is a stateless `Component`. Use a `Component` for views that have their own state
+/// or need lifecycle hooks.
+///
 /// TODO: VirtualNode::from(impl View)
 pub trait View {
     /// Render a VirtualNode
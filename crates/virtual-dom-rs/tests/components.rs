@@ -0,0 +1,172 @@
+//! Ensure that components re-render their own part of the DOM, call their lifecycle hooks
+//! with their root element and keep the event handlers of their latest render.
+
+#![feature(proc_macro_hygiene)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use virtual_dom_rs::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test;
+use wasm_bindgen_test::*;
+use web_sys::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+struct Counter {
+    count: u32,
+    /// Every lifecycle hook that was called, along with the element's id
+    hooks: Rc<RefCell<Vec<String>>>,
+}
+
+enum Msg {
+    Increment,
+}
+
+impl Component for Counter {
+    type Msg = Msg;
+
+    fn update(&mut self, msg: Msg) -> bool {
+        match msg {
+            Msg::Increment => self.count += 1,
+        };
+
+        true
+    }
+
+    fn view(&self, _handle: &ComponentHandle<Self>) -> VirtualNode {
+        let count = &self.count.to_string();
+
+        html! { <div id="counter"> <span>{ text!(count) }</span> </div> }
+    }
+
+    fn mounted(&mut self, element: &Element) {
+        self.hooks
+            .borrow_mut()
+            .push(format!("mounted {}", element.id()));
+    }
+
+    fn updated(&mut self, element: &Element) {
+        self.hooks
+            .borrow_mut()
+            .push(format!("updated {}", element.id()));
+    }
+
+    fn before_unmount(&mut self, element: &Element) {
+        self.hooks
+            .borrow_mut()
+            .push(format!("before_unmount {}", element.id()));
+    }
+}
+
+#[wasm_bindgen_test]
+fn lifecycle() {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let body = document.body().unwrap();
+
+    let hooks = Rc::new(RefCell::new(vec![]));
+    let counter = ComponentHandle::new(Counter {
+        count: 0,
+        hooks: Rc::clone(&hooks),
+    });
+
+    counter.append_to(&body);
+    let element = document.get_element_by_id("counter").unwrap();
+
+    counter.send(Msg::Increment);
+    counter.send(Msg::Increment);
+
+    // Patched in place instead of being replaced
+    assert_eq!(element.text_content().unwrap(), "2");
+    assert!(document.get_element_by_id("counter").unwrap() == element);
+
+    counter.unmount();
+    assert!(document.get_element_by_id("counter").is_none());
+
+    assert_eq!(
+        *hooks.borrow(),
+        vec![
+            "mounted counter",
+            "updated counter",
+            "updated counter",
+            "before_unmount counter",
+        ]
+    );
+}
+
+/// Only renders its button after a re-render, and the button's handler captures the number of
+/// clicks from the render that created it
+struct Clicker {
+    clicks: u32,
+    show_button: bool,
+}
+
+enum ClickerMsg {
+    ShowButton,
+    SetClicks(u32),
+}
+
+impl Component for Clicker {
+    type Msg = ClickerMsg;
+
+    fn update(&mut self, msg: ClickerMsg) -> bool {
+        match msg {
+            ClickerMsg::ShowButton => self.show_button = true,
+            ClickerMsg::SetClicks(clicks) => self.clicks = clicks,
+        };
+
+        true
+    }
+
+    fn view(&self, handle: &ComponentHandle<Self>) -> VirtualNode {
+        let mut children = vec![];
+
+        if self.show_button {
+            let handle = handle.clone();
+            let clicks = self.clicks;
+
+            children.push(html! {
+                <button
+                  id="clicker-button"
+                  onclick=move |_: MouseEvent| handle.send(ClickerMsg::SetClicks(clicks + 1))
+                >
+                </button>
+            });
+        }
+
+        html! { <div id="clicker">{ children }</div> }
+    }
+}
+
+#[wasm_bindgen_test]
+fn handlers_added_by_a_re_render() {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let body = document.body().unwrap();
+
+    let clicker = ComponentHandle::new(Clicker {
+        clicks: 0,
+        show_button: false,
+    });
+    clicker.append_to(&body);
+
+    clicker.send(ClickerMsg::ShowButton);
+
+    let click = || {
+        let button: EventTarget = document
+            .get_element_by_id("clicker-button")
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+
+        button.dispatch_event(&Event::new("click").unwrap()).unwrap();
+    };
+
+    click();
+    assert_eq!(clicker.component().clicks, 1);
+
+    // The handler from the second render, which captured one click, replaced the first one
+    click();
+    assert_eq!(clicker.component().clicks, 2);
+
+    clicker.unmount();
+}
@@ -0,0 +1,602 @@
+//! Turn a string of HTML into a `VirtualNode`.
+//!
+//! Useful when you have existing markup (CMS content, email templates, fixture files) that you
+//! want to render as a virtual dom, or when you want to compare a rendered view against an
+//! expected HTML snippet in a unit test.
+
+use crate::{VElement, VirtualNode, SELF_CLOSING_TAGS};
+use std::fmt;
+
+impl VirtualNode {
+    /// Parse a string of HTML into a `VirtualNode`.
+    ///
+    /// Comments and `<!DOCTYPE>` declarations are skipped, entities such as `&amp;` are decoded
+    /// and self closing tags such as `<br>` don't need a closing tag.
+    ///
+    /// Whitespace that contains a line break is removed from the start and end of text, and
+    /// text that is only made up of that sort of whitespace is dropped. So you can indent your
+    /// HTML snippets without the indentation ending up in your text nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let node = VirtualNode::from_html(r#"<div id="app"><br> Hello &amp; welcome </div>"#)?;
+    ///
+    /// assert_eq!(node.to_string(), r#"<div id="app"><br> Hello &amp; welcome </div>"#);
+    /// ```
+    pub fn from_html(html: &str) -> Result<VirtualNode, ParseError> {
+        HtmlParser::new(html).parse()
+    }
+}
+
+/// An error that occurred while parsing HTML.
+///
+/// Positions are byte offsets into the string that was being parsed.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// There weren't any nodes in the HTML.
+    Empty,
+    /// There was more than one node at the root level, such as `<div></div><span></span>`.
+    /// A `VirtualNode` can only have one root.
+    MultipleRootNodes { pos: usize },
+    /// The HTML ended in the middle of a tag, comment or attribute.
+    UnexpectedEof,
+    /// An element was opened but never closed, such as the `em` in `<div><em></div>`.
+    UnclosedTag { tag: String, pos: usize },
+    /// A closing tag didn't match the most recently opened tag.
+    MismatchedClosingTag {
+        expected: String,
+        found: String,
+        pos: usize,
+    },
+    /// A closing tag was found without any open tag, or for a self closing tag such as `</br>`.
+    UnexpectedClosingTag { tag: String, pos: usize },
+    /// We expected a tag or attribute name but didn't find one.
+    ExpectedName { pos: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "The HTML did not contain any nodes"),
+            ParseError::MultipleRootNodes { pos } => write!(
+                f,
+                "Found a second root node at position {}. HTML must have a single root node",
+                pos
+            ),
+            ParseError::UnexpectedEof => write!(f, "Unexpected end of HTML"),
+            ParseError::UnclosedTag { tag, pos } => {
+                write!(f, "<{}> at position {} was never closed", tag, pos)
+            }
+            ParseError::MismatchedClosingTag {
+                expected,
+                found,
+                pos,
+            } => write!(
+                f,
+                "Wrong closing tag </{}> at position {}. Expected </{}>",
+                found, pos, expected
+            ),
+            ParseError::UnexpectedClosingTag { tag, pos } => write!(
+                f,
+                "Unexpected closing tag </{}> at position {}",
+                tag, pos
+            ),
+            ParseError::ExpectedName { pos } => {
+                write!(f, "Expected a tag or attribute name at position {}", pos)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+struct HtmlParser<'a> {
+    html: &'a str,
+    /// Our current byte offset into the html
+    pos: usize,
+}
+
+/// An element that has been opened but not yet closed, along with the position of its
+/// opening tag.
+struct OpenElement {
+    element: VElement,
+    pos: usize,
+}
+
+impl<'a> HtmlParser<'a> {
+    fn new(html: &'a str) -> HtmlParser<'a> {
+        HtmlParser { html, pos: 0 }
+    }
+
+    fn parse(&mut self) -> Result<VirtualNode, ParseError> {
+        let mut roots: Vec<(VirtualNode, usize)> = vec![];
+        let mut open_elements: Vec<OpenElement> = vec![];
+
+        while self.pos < self.html.len() {
+            let start = self.pos;
+
+            let node = if self.rest().starts_with("<!--") {
+                self.skip_comment()?;
+                None
+            } else if self.rest().starts_with("<!") || self.rest().starts_with("<?") {
+                // <!DOCTYPE html>
+                self.skip_past(">")?;
+                None
+            } else if self.rest().starts_with("</") {
+                let tag = self.parse_closing_tag()?;
+
+                let open = match open_elements.pop() {
+                    Some(open) => open,
+                    None => return Err(ParseError::UnexpectedClosingTag { tag, pos: start }),
+                };
+
+                if open.element.tag != tag {
+                    return Err(ParseError::MismatchedClosingTag {
+                        expected: open.element.tag,
+                        found: tag,
+                        pos: start,
+                    });
+                }
+
+                Some((VirtualNode::Element(open.element), open.pos))
+            } else if self.peek_open_tag() {
+                let (mut element, is_self_closing) = self.parse_open_tag()?;
+
+                if is_self_closing {
+                    Some((VirtualNode::Element(element), start))
+                } else if element.is_raw_text() {
+                    // The contents of a <script> are kept as is. So `a < b` won't be treated
+                    // as the start of a `<b>` tag.
+                    let text = self.parse_raw_text(&element.tag)?;
+                    if !text.is_empty() {
+                        element.children.push(VirtualNode::text(&text));
+                    }
+                    Some((VirtualNode::Element(element), start))
+                } else {
+                    open_elements.push(OpenElement {
+                        element,
+                        pos: start,
+                    });
+                    None
+                }
+            } else {
+                let text = self.parse_text();
+
+                // Whitespace around the root node isn't part of the root node
+                if open_elements.is_empty() && text.trim().is_empty() {
+                    continue;
+                }
+
+                normalize_whitespace(&text).map(|text| (VirtualNode::text(&text), start))
+            };
+
+            if let Some((node, pos)) = node {
+                match open_elements.last_mut() {
+                    Some(parent) => parent.element.children.push(node),
+                    None => roots.push((node, pos)),
+                }
+            }
+        }
+
+        if let Some(unclosed) = open_elements.pop() {
+            return Err(ParseError::UnclosedTag {
+                tag: unclosed.element.tag,
+                pos: unclosed.pos,
+            });
+        }
+
+        if roots.len() > 1 {
+            return Err(ParseError::MultipleRootNodes { pos: roots[1].1 });
+        }
+
+        roots.pop().map(|root| root.0).ok_or(ParseError::Empty)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.html[self.pos..]
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// `<div` is the start of a tag, but `< div` or `a < b` is text.
+    fn peek_open_tag(&self) -> bool {
+        let mut chars = self.rest().chars();
+        chars.next() == Some('<') && chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.html.len() - trimmed.len();
+    }
+
+    /// Move to right after the next occurrence of `pattern`.
+    fn skip_past(&mut self, pattern: &str) -> Result<(), ParseError> {
+        match self.rest().find(pattern) {
+            Some(idx) => {
+                self.pos += idx + pattern.len();
+                Ok(())
+            }
+            None => Err(ParseError::UnexpectedEof),
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), ParseError> {
+        self.pos += "<!--".len();
+        self.skip_past("-->")
+    }
+
+    /// Parse a tag or attribute name such as `div` or `data-id`
+    fn parse_name(&mut self) -> Result<String, ParseError> {
+        let len = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=')
+            .unwrap_or(self.rest().len());
+
+        if len == 0 {
+            return Err(ParseError::ExpectedName { pos: self.pos });
+        }
+
+        let name = self.rest()[..len].to_lowercase();
+        self.pos += len;
+
+        Ok(name)
+    }
+
+    /// `<div id="app" hidden>` or `<br />`
+    ///
+    /// Returns the element along with whether or not it has no children / closing tag.
+    fn parse_open_tag(&mut self) -> Result<(VElement, bool), ParseError> {
+        self.pos += "<".len();
+
+        let tag = self.parse_name()?;
+        let mut element = VElement::new(tag);
+
+        loop {
+            self.skip_whitespace();
+
+            match self.peek_char() {
+                None => return Err(ParseError::UnexpectedEof),
+                Some('>') => {
+                    self.pos += 1;
+                    let is_self_closing = element.is_self_closing();
+                    return Ok((element, is_self_closing));
+                }
+                Some('/') if self.rest().starts_with("/>") => {
+                    self.pos += 2;
+                    return Ok((element, true));
+                }
+                Some('/') => {
+                    self.pos += 1;
+                }
+                Some(_) => {
+                    let (name, value) = self.parse_attribute()?;
+                    element.props.insert(name, value);
+                }
+            }
+        }
+    }
+
+    /// `id="app"`, `id='app'`, `id=app` or `hidden`
+    fn parse_attribute(&mut self) -> Result<(String, String), ParseError> {
+        let name = self.parse_name()?;
+
+        self.skip_whitespace();
+        if self.peek_char() != Some('=') {
+            return Ok((name, "".to_string()));
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+
+        let raw_value = match self.peek_char() {
+            None => return Err(ParseError::UnexpectedEof),
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.pos += 1;
+                let len = match self.rest().find(quote) {
+                    Some(len) => len,
+                    None => return Err(ParseError::UnexpectedEof),
+                };
+                let value = &self.rest()[..len];
+                self.pos += len + 1;
+                value
+            }
+            Some(_) => {
+                let len = self
+                    .rest()
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(self.rest().len());
+                let value = &self.rest()[..len];
+                self.pos += len;
+                value
+            }
+        };
+
+        Ok((name, decode_entities(raw_value)))
+    }
+
+    /// `</div>`
+    fn parse_closing_tag(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+        self.pos += "</".len();
+
+        let tag = self.parse_name()?;
+        self.skip_whitespace();
+
+        if self.peek_char() != Some('>') {
+            return Err(ParseError::UnexpectedEof);
+        }
+        self.pos += 1;
+
+        if SELF_CLOSING_TAGS.contains(tag.as_str()) {
+            return Err(ParseError::UnexpectedClosingTag { tag, pos: start });
+        }
+
+        Ok(tag)
+    }
+
+    /// Everything up until the next tag or comment
+    fn parse_text(&mut self) -> String {
+        let mut len = 0;
+
+        // A `<` that isn't followed by a tag name, `/` or `!` is just text
+        for (idx, c) in self.rest().char_indices() {
+            if c == '<' && idx > 0 {
+                let after = &self.rest()[idx + 1..];
+                let is_markup = after.starts_with('/')
+                    || after.starts_with('!')
+                    || after.starts_with('?')
+                    || after.chars().next().map_or(false, |c| c.is_ascii_alphabetic());
+                if is_markup {
+                    break;
+                }
+            }
+            len = idx + c.len_utf8();
+        }
+
+        let text = &self.rest()[..len];
+        self.pos += len;
+
+        decode_entities(text)
+    }
+
+    /// The contents of a `<script>` or `<style>` tag, up until (and including) its closing tag.
+    fn parse_raw_text(&mut self, tag: &str) -> Result<String, ParseError> {
+        let closing_tag = format!("</{}", tag);
+
+        let len = match self.rest().to_ascii_lowercase().find(&closing_tag) {
+            Some(len) => len,
+            None => return Err(ParseError::UnexpectedEof),
+        };
+
+        let text = self.rest()[..len].to_string();
+        self.pos += len;
+
+        self.parse_closing_tag()?;
+
+        Ok(text)
+    }
+}
+
+/// Apply the same whitespace rules as the `html!` macro to a text node.
+///
+/// Returns `None` if nothing is left of the text.
+fn normalize_whitespace(text: &str) -> Option<String> {
+    let mut text = text;
+
+    let leading = &text[..text.len() - text.trim_start().len()];
+    if leading.contains('\n') {
+        text = text.trim_start();
+    }
+
+    let trailing = &text[text.trim_end().len()..];
+    if trailing.contains('\n') {
+        text = text.trim_end();
+    }
+
+    if text.is_empty() {
+        return None;
+    }
+
+    Some(text.to_string())
+}
+
+/// `&lt;b&gt;` -> `<b>`
+///
+/// Entities that we don't know about are left untouched.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        decoded += &rest[..amp];
+        rest = &rest[amp..];
+
+        let entity = rest
+            .find(';')
+            .filter(|semicolon| *semicolon <= 10)
+            .and_then(|semicolon| decode_entity(&rest[1..semicolon]).map(|c| (c, semicolon)));
+
+        match entity {
+            Some((c, semicolon)) => {
+                decoded.push(c);
+                rest = &rest[semicolon + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded += rest;
+    decoded
+}
+
+/// `amp` -> `&`, `#60` -> `<`, `#x3C` -> `<`
+fn decode_entity(entity: &str) -> Option<char> {
+    if entity.starts_with("#x") || entity.starts_with("#X") {
+        return u32::from_str_radix(&entity[2..], 16)
+            .ok()
+            .and_then(std::char::from_u32);
+    }
+
+    if entity.starts_with('#') {
+        return entity[1..].parse().ok().and_then(std::char::from_u32);
+    }
+
+    let c = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "laquo" => '«',
+        "raquo" => '»',
+        "times" => '×',
+        _ => return None,
+    };
+
+    Some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(html: &str) -> VirtualNode {
+        VirtualNode::from_html(html).unwrap()
+    }
+
+    #[test]
+    fn nested_elements() {
+        let mut span = VElement::new("span");
+        span.children.push(VirtualNode::text("Hello"));
+
+        let mut div = VElement::new("div");
+        div.props.insert("id".to_string(), "app".to_string());
+        div.children.push(span.into());
+
+        assert_eq!(parse(r#"<div id="app"><span>Hello</span></div>"#), div.into());
+    }
+
+    #[test]
+    fn attributes() {
+        let node = parse(r#"<input type='text' value=hi disabled data-id="1">"#);
+        let props = &node.as_velement_ref().unwrap().props;
+
+        assert_eq!(props.get("type").unwrap(), "text");
+        assert_eq!(props.get("value").unwrap(), "hi");
+        assert_eq!(props.get("disabled").unwrap(), "");
+        assert_eq!(props.get("data-id").unwrap(), "1");
+    }
+
+    #[test]
+    fn self_closing_tags() {
+        let node = parse("<div><br><img src=\"a.png\" /><hr/></div>");
+        let children = &node.as_velement_ref().unwrap().children;
+
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[0], VirtualNode::element("br"));
+    }
+
+    #[test]
+    fn entities() {
+        let node = parse(r#"<p title="&quot;hi&quot;">a &lt; b &amp;&amp; c &#62; d &#x41; &bogus;</p>"#);
+        let element = node.as_velement_ref().unwrap();
+
+        assert_eq!(element.props.get("title").unwrap(), r#""hi""#);
+        assert_eq!(
+            element.children[0].as_vtext_ref().unwrap().text,
+            "a < b && c > d A &bogus;"
+        );
+    }
+
+    #[test]
+    fn comments_and_doctype() {
+        let node = parse("<!DOCTYPE html><!-- root --><div><!-- <span></span> --></div>");
+
+        assert_eq!(node, VirtualNode::element("div"));
+    }
+
+    #[test]
+    fn indentation_whitespace() {
+        let node = parse(
+            r#"
+        <div>
+            <span> Hello world </span>
+            <b>a</b> <i>b</i>
+        </div>
+        "#,
+        );
+        let children = &node.as_velement_ref().unwrap().children;
+
+        assert_eq!(children.len(), 4);
+        assert_eq!(
+            children[0].as_velement_ref().unwrap().children[0],
+            VirtualNode::text(" Hello world ")
+        );
+        assert_eq!(children[2], VirtualNode::text(" "));
+    }
+
+    #[test]
+    fn raw_text_elements() {
+        let node = parse("<script>if (a < b && c) {}</script>");
+
+        assert_eq!(
+            node.as_velement_ref().unwrap().children[0],
+            VirtualNode::text("if (a < b && c) {}")
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let html = r#"<div><p>a &lt; b &amp; c</p><br><input value="&quot;quoted&quot;"></div>"#;
+
+        assert_eq!(parse(html).to_string(), html);
+        assert_eq!(parse(&parse(html).to_string()), parse(html));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(VirtualNode::from_html("  "), Err(ParseError::Empty));
+        assert_eq!(
+            VirtualNode::from_html("<div><em></div>"),
+            Err(ParseError::MismatchedClosingTag {
+                expected: "em".to_string(),
+                found: "div".to_string(),
+                pos: 9
+            })
+        );
+        assert_eq!(
+            VirtualNode::from_html("<div>"),
+            Err(ParseError::UnclosedTag {
+                tag: "div".to_string(),
+                pos: 0
+            })
+        );
+        assert_eq!(
+            VirtualNode::from_html("<br></br>"),
+            Err(ParseError::UnexpectedClosingTag {
+                tag: "br".to_string(),
+                pos: 4
+            })
+        );
+        assert_eq!(
+            VirtualNode::from_html("<b></b><i></i>"),
+            Err(ParseError::MultipleRootNodes { pos: 7 })
+        );
+        assert_eq!(
+            VirtualNode::from_html("<div id=\"app"),
+            Err(ParseError::UnexpectedEof)
+        );
+    }
+}
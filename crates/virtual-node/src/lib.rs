@@ -15,11 +15,28 @@ use std::rc::Rc;
 
 pub mod virtual_node_test_utils;
 
-use web_sys::{self, Text, Element, Node, EventTarget};
+mod html_parser;
+pub use self::html_parser::ParseError;
 
+mod selector;
+
+mod render;
+pub use self::render::{RenderMode, RenderOptions};
+
+use web_sys::{self, Text, Element, Node};
+
+#[cfg(target_arch = "wasm32")]
+use web_sys::EventTarget;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsCast;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsValue;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::any::Any;
+#[cfg(not(target_arch = "wasm32"))]
+use std::cell::RefCell;
+
 use lazy_static::lazy_static;
 
 use std::ops::Deref;
@@ -49,7 +66,8 @@ lazy_static! {
 /// update the real DOM with your latest tree of virtual nodes (virtual dom).
 ///
 /// Or on the server side you'll just call `.to_string()` on your root virtual node
-/// in order to recursively render the node and all of its children.
+/// in order to recursively render the node and all of its children. Use `.to_html` with
+/// `RenderOptions` for pretty printed or minified HTML.
 ///
 /// TODO: Make all of these fields private and create accessor methods
 /// TODO: Create a builder to create instances of VirtualNode::Element with
@@ -180,6 +198,12 @@ impl VElement {
         SELF_CLOSING_TAGS.contains(self.tag.as_str())
     }
 
+    /// Whether or not the text inside of this element is something other than HTML, such as the
+    /// JavaScript in a <script> tag or the CSS in a <style> tag.
+    pub fn is_raw_text(&self) -> bool {
+        self.tag == "script" || self.tag == "style"
+    }
+
     /// Build a DOM element by recursively creating DOM nodes for this element and it's
     /// children, it's children's children, etc.
     pub fn create_element_node(&self) -> CreatedNode<Element> {
@@ -195,32 +219,8 @@ impl VElement {
         });
 
         if self.events.0.len() > 0 {
-            let unique_id = create_unique_identifier();
-
-            element
-                .set_attribute("data-vdom-id".into(), &unique_id.to_string())
-                .expect("Could not set attribute on element");
-
-            closures.insert(unique_id, vec![]);
-
-            self.events.0.iter().for_each(|(onevent, callback)| {
-                // onclick -> click
-                let event = &onevent[2..];
-
-                let current_elem: &EventTarget = element.dyn_ref().unwrap();
-
-                current_elem
-                    .add_event_listener_with_callback(
-                        event,
-                        callback.as_ref().as_ref().unchecked_ref(),
-                    )
-                    .unwrap();
-
-                closures
-                    .get_mut(&unique_id)
-                    .unwrap()
-                    .push(Rc::clone(callback));
-            });
+            let (unique_id, element_closures) = self.events.attach(&element);
+            closures.insert(unique_id, element_closures);
         }
 
         let mut previous_node_was_text = false;
@@ -380,30 +380,14 @@ impl fmt::Debug for VText {
 impl fmt::Display for VElement {
     // Turn a VElement and all of it's children (recursively) into an HTML string
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "<{}", self.tag).unwrap();
-
-        for (prop, value) in self.props.iter() {
-            write!(f, r#" {}="{}""#, prop, value)?;
-        }
-
-        write!(f, ">")?;
-
-        for child in self.children.iter() {
-            write!(f, "{}", child.to_string())?;
-        }
-
-        if !self.is_self_closing() {
-            write!(f, "</{}>", self.tag)?;
-        }
-
-        Ok(())
+        write!(f, "{}", self.to_html(&RenderOptions::default()))
     }
 }
 
 // Turn a VText into an HTML string
 impl fmt::Display for VText {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.text)
+        write!(f, "{}", render::escape_text(&self.text))
     }
 }
 
@@ -419,12 +403,100 @@ impl fmt::Display for VirtualNode {
 
 /// Box<dyn AsRef<JsValue>>> is our js_sys::Closure. Stored this way to allow us to store
 /// any Closure regardless of the arguments.
+#[cfg(target_arch = "wasm32")]
 pub type DynClosure = Rc<dyn AsRef<JsValue>>;
 
+/// Outside of wasm32 there is no js_sys::Closure, so we store event handlers as regular Rust
+/// closures that receive a type erased event. This lets unit tests call them
+/// with `VirtualNode.trigger_event`.
+#[cfg(not(target_arch = "wasm32"))]
+pub type DynClosure = Rc<RefCell<dyn FnMut(Box<dyn Any>)>>;
+
 /// We need a custom implementation of fmt::Debug since JsValue doesn't
 /// implement debug.
 pub struct Events(pub HashMap<String, DynClosure>);
 
+#[cfg(not(target_arch = "wasm32"))]
+impl Events {
+    /// Store a handler for an event such as `onclick`.
+    ///
+    /// The `html!` macro uses this for closures outside of wasm32. Whatever value a test
+    /// passes to `VirtualNode.trigger_event` must be of the type that the closure accepts.
+    pub fn insert_handler<T, F>(&mut self, onevent: &str, mut handler: F)
+    where
+        T: 'static,
+        F: FnMut(T) + 'static,
+    {
+        let onevent_name = onevent.to_string();
+
+        let handler = move |event: Box<dyn Any>| match event.downcast::<T>() {
+            Ok(event) => handler(*event),
+            Err(_) => panic!(
+                "The {} handler expects an event of type {}",
+                onevent_name,
+                std::any::type_name::<T>()
+            ),
+        };
+
+        self.0
+            .insert(onevent.to_string(), Rc::new(RefCell::new(handler)));
+    }
+}
+
+impl Events {
+    /// Listen to each of our events on the element, such as `click` for `onclick`.
+    ///
+    /// The element gets a `data-vdom-id` if it doesn't have one yet. We return that id along
+    /// with our closures, which need to be kept alive for as long as they're listening.
+    pub fn attach(&self, element: &Element) -> (u32, Vec<DynClosure>) {
+        let unique_id = element
+            .get_attribute("data-vdom-id")
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_else(|| {
+                let unique_id = create_unique_identifier();
+                element
+                    .set_attribute("data-vdom-id", &unique_id.to_string())
+                    .expect("Could not set attribute on element");
+                unique_id
+            });
+
+        // Outside of wasm32 our callbacks are plain Rust closures, so there is nothing
+        // to attach to the DOM.
+        #[cfg(target_arch = "wasm32")]
+        self.0.iter().for_each(|(onevent, callback)| {
+            // onclick -> click
+            let event = &onevent[2..];
+
+            let current_elem: &EventTarget = element.dyn_ref().unwrap();
+
+            current_elem
+                .add_event_listener_with_callback(event, callback.as_ref().as_ref().unchecked_ref())
+                .unwrap();
+        });
+
+        (unique_id, self.0.values().map(Rc::clone).collect())
+    }
+
+    /// Stop listening to the events that `attach` added to the element
+    #[cfg(target_arch = "wasm32")]
+    pub fn detach(&self, element: &Element) {
+        self.0.iter().for_each(|(onevent, callback)| {
+            let event = &onevent[2..];
+
+            let current_elem: &EventTarget = element.dyn_ref().unwrap();
+
+            let _ = current_elem.remove_event_listener_with_callback(
+                event,
+                callback.as_ref().as_ref().unchecked_ref(),
+            );
+        });
+    }
+
+    /// Stop listening to the events that `attach` added to the element
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn detach(&self, _element: &Element) {}
+}
+
 impl PartialEq for Events {
     // TODO: What should happen here..? And why?
     fn eq(&self, _rhs: &Self) -> bool {
@@ -452,6 +524,18 @@ mod tests {
         assert_eq!(&node.to_string(), "<br>");
     }
 
+    #[test]
+    fn escapes_text_and_attributes() {
+        let mut div = VElement::new("div");
+        div.props.insert("title".to_string(), r#"Say "hi""#.to_string());
+        div.children.push(VirtualNode::text("<b> & </b>"));
+
+        assert_eq!(
+            &div.to_string(),
+            r#"<div title="Say &quot;hi&quot;">&lt;b&gt; &amp; &lt;/b&gt;</div>"#
+        );
+    }
+
     // TODO: Use html_macro as dev dependency and uncomment
     //    #[test]
     //    fn to_string() {
@@ -0,0 +1,357 @@
+//! Turn a VirtualNode into an HTML string, such as when rendering on the server.
+
+use crate::{VElement, VirtualNode};
+
+/// How `VirtualNode.to_html` lays out the HTML that it renders.
+///
+/// `RenderOptions::default()` is what `.to_string()` uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// Where whitespace goes
+    pub mode: RenderMode,
+    /// Render self closing tags XHTML style, i.e. `<br />` instead of `<br>`
+    pub xhtml: bool,
+    /// Render attributes in alphabetical order instead of whatever order they are stored in,
+    /// so that the same node always renders the same string.
+    pub sort_attributes: bool,
+}
+
+/// Where whitespace goes when rendering HTML.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    /// Everything on one line, with text exactly as it appears in your nodes.
+    Compact,
+    /// One element per line, indented by two spaces for every level of nesting.
+    ///
+    /// Elements that only contain text stay on one line, `<span>Like this</span>`.
+    /// The children of elements where whitespace matters such as `<pre>` and `<textarea>`
+    /// are rendered compactly so that their content doesn't change.
+    Pretty,
+    /// Everything on one line with runs of whitespace in text collapsed into a single space
+    /// and empty attributes rendered as `disabled` instead of `disabled=""`.
+    Minified,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            mode: RenderMode::Compact,
+            xhtml: false,
+            sort_attributes: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Indented HTML that is easy to read, such as when debugging server side rendering.
+    pub fn pretty() -> Self {
+        RenderOptions {
+            mode: RenderMode::Pretty,
+            ..RenderOptions::default()
+        }
+    }
+
+    /// As few bytes as possible, such as for server side rendering in production.
+    pub fn minified() -> Self {
+        RenderOptions {
+            mode: RenderMode::Minified,
+            ..RenderOptions::default()
+        }
+    }
+}
+
+impl VirtualNode {
+    /// Render this node and all of its descendants into an HTML string.
+    ///
+    /// ```ignore
+    /// use virtual_dom_rs::{RenderOptions, VirtualNode};
+    ///
+    /// let node = html! { <div> <br> </div> };
+    ///
+    /// assert_eq!(node.to_html(&RenderOptions::pretty()), "<div>\n  <br>\n</div>\n");
+    /// assert_eq!(
+    ///     node.to_html(&RenderOptions { xhtml: true, ..RenderOptions::default() }),
+    ///     "<div><br /></div>"
+    /// );
+    /// ```
+    pub fn to_html(&self, options: &RenderOptions) -> String {
+        let mut renderer = Renderer {
+            options,
+            html: String::new(),
+        };
+
+        renderer.push_node(self, renderer.root_depth(), None, false);
+
+        renderer.html
+    }
+}
+
+impl VElement {
+    /// Render this element and all of its descendants into an HTML string.
+    pub fn to_html(&self, options: &RenderOptions) -> String {
+        let mut renderer = Renderer {
+            options,
+            html: String::new(),
+        };
+
+        renderer.push_element(self, renderer.root_depth(), false);
+
+        renderer.html
+    }
+
+    /// Whether or not the whitespace inside of this element affects what it displays.
+    fn preserves_whitespace(&self) -> bool {
+        self.is_raw_text() || self.tag == "pre" || self.tag == "textarea"
+    }
+}
+
+struct Renderer<'a> {
+    options: &'a RenderOptions,
+    html: String,
+}
+
+impl<'a> Renderer<'a> {
+    /// In pretty mode nodes get laid out on their own lines at some depth, otherwise
+    /// everything is inline (`None`).
+    fn root_depth(&self) -> Option<usize> {
+        match self.options.mode {
+            RenderMode::Pretty => Some(0),
+            _ => None,
+        }
+    }
+
+    fn push_node(
+        &mut self,
+        node: &VirtualNode,
+        depth: Option<usize>,
+        parent: Option<&VElement>,
+        preserve_whitespace: bool,
+    ) {
+        let text = match node {
+            VirtualNode::Element(element) => {
+                return self.push_element(element, depth, preserve_whitespace);
+            }
+            VirtualNode::Text(text) => &text.text,
+        };
+
+        // The contents of <script> and <style> tags are not HTML, so we don't escape them
+        let text = match parent {
+            Some(parent) if parent.is_raw_text() => text.to_string(),
+            _ => escape_text(text),
+        };
+
+        let text = match self.options.mode {
+            RenderMode::Minified if !preserve_whitespace => collapse_whitespace(&text),
+            _ => text,
+        };
+
+        self.push_indent(depth);
+        self.html.push_str(&text);
+        self.push_newline(depth);
+    }
+
+    fn push_element(&mut self, element: &VElement, depth: Option<usize>, preserve_whitespace: bool) {
+        self.push_indent(depth);
+        self.push_open_tag(element);
+
+        if element.is_self_closing() {
+            self.push_newline(depth);
+            return;
+        }
+
+        let preserve_whitespace = preserve_whitespace || element.preserves_whitespace();
+
+        // Putting the children of mixed content such as `Hello <em>World</em>` on their own
+        // lines would add whitespace between its text and elements
+        let children_inline = depth.is_none()
+            || preserve_whitespace
+            || element.children.is_empty()
+            || element.children.iter().any(|child| child.as_vtext_ref().is_some());
+
+        match depth {
+            Some(depth) if !children_inline => {
+                self.html.push('\n');
+
+                for child in element.children.iter() {
+                    self.push_node(child, Some(depth + 1), Some(element), preserve_whitespace);
+                }
+
+                self.push_indent(Some(depth));
+            }
+            _ => {
+                for child in element.children.iter() {
+                    self.push_node(child, None, Some(element), preserve_whitespace);
+                }
+            }
+        };
+
+        self.html.push_str(&format!("</{}>", element.tag));
+        self.push_newline(depth);
+    }
+
+    fn push_open_tag(&mut self, element: &VElement) {
+        let mut props: Vec<(&String, &String)> = element.props.iter().collect();
+        if self.options.sort_attributes {
+            props.sort();
+        }
+
+        self.html.push_str(&format!("<{}", element.tag));
+
+        for (prop, value) in props {
+            match self.options.mode {
+                // XHTML requires every attribute to have a value
+                RenderMode::Minified if value.is_empty() && !self.options.xhtml => {
+                    self.html.push_str(&format!(" {}", prop));
+                }
+                _ => {
+                    self.html
+                        .push_str(&format!(r#" {}="{}""#, prop, escape_attribute(value)));
+                }
+            }
+        }
+
+        if element.is_self_closing() && self.options.xhtml {
+            self.html.push_str(" />");
+        } else {
+            self.html.push('>');
+        }
+    }
+
+    fn push_indent(&mut self, depth: Option<usize>) {
+        if let Some(depth) = depth {
+            self.html.push_str(&"  ".repeat(depth));
+        }
+    }
+
+    fn push_newline(&mut self, depth: Option<usize>) {
+        if depth.is_some() {
+            self.html.push('\n');
+        }
+    }
+}
+
+/// `a < b` -> `a &lt; b` so that text can't be mistaken for markup.
+pub(crate) fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// `say "hi"` -> `say &quot;hi&quot;` so that a value can't end its attribute early.
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+/// `Hello \n    World` -> `Hello World`
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut previous_was_whitespace = false;
+
+    for character in text.chars() {
+        if character.is_whitespace() {
+            if !previous_was_whitespace {
+                collapsed.push(' ');
+            }
+            previous_was_whitespace = true;
+        } else {
+            collapsed.push(character);
+            previous_was_whitespace = false;
+        }
+    }
+
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node() -> VirtualNode {
+        VirtualNode::from_html(
+            r#"<div id="app" class="main"><h1>Hello<em>World</em></h1><input disabled><p>a &lt;   b
+            c</p><pre><b>x</b>  y</pre></div>"#,
+        )
+        .unwrap()
+    }
+
+    fn sorted(options: RenderOptions) -> RenderOptions {
+        RenderOptions {
+            sort_attributes: true,
+            ..options
+        }
+    }
+
+    #[test]
+    fn compact() {
+        assert_eq!(
+            node().to_html(&sorted(RenderOptions::default())),
+            "<div class=\"main\" id=\"app\"><h1>Hello<em>World</em></h1><input disabled=\"\"><p>a &lt;   b\n            c</p><pre><b>x</b>  y</pre></div>"
+        );
+    }
+
+    #[test]
+    fn pretty() {
+        let expected = r#"<div class="main" id="app">
+  <h1>Hello<em>World</em></h1>
+  <input disabled="">
+  <p>a &lt;   b
+            c</p>
+  <pre><b>x</b>  y</pre>
+</div>
+"#;
+
+        assert_eq!(node().to_html(&sorted(RenderOptions::pretty())), expected);
+    }
+
+    #[test]
+    fn pretty_mixed_content() {
+        let node = VirtualNode::from_html(
+            "<div><h1>Hello<em>World</em></h1><ul><li>One <b>two</b></li></ul></div>",
+        )
+        .unwrap();
+
+        let expected = r#"<div>
+  <h1>Hello<em>World</em></h1>
+  <ul>
+    <li>One <b>two</b></li>
+  </ul>
+</div>
+"#;
+
+        assert_eq!(node.to_html(&RenderOptions::pretty()), expected);
+    }
+
+    #[test]
+    fn minified() {
+        assert_eq!(
+            node().to_html(&sorted(RenderOptions::minified())),
+            r#"<div class="main" id="app"><h1>Hello<em>World</em></h1><input disabled><p>a &lt; b c</p><pre><b>x</b>  y</pre></div>"#
+        );
+    }
+
+    #[test]
+    fn xhtml() {
+        let mut div = VElement::new("div");
+        div.children.push(VirtualNode::element("br"));
+        let mut img = VElement::new("img");
+        img.props.insert("hidden".to_string(), "".to_string());
+        div.children.push(img.into());
+
+        let options = RenderOptions {
+            xhtml: true,
+            ..RenderOptions::minified()
+        };
+
+        assert_eq!(div.to_html(&options), r#"<div><br /><img hidden="" /></div>"#);
+    }
+
+    #[test]
+    fn raw_text_is_not_escaped() {
+        let node = VirtualNode::from_html("<script>if (a < b) {}</script>").unwrap();
+
+        assert_eq!(
+            node.to_html(&RenderOptions::pretty()),
+            "<script>if (a < b) {}</script>\n"
+        );
+    }
+}
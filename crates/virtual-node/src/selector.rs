@@ -0,0 +1,465 @@
+//! A small subset of CSS selectors that we can match against elements in a virtual dom.
+//!
+//! Supported:
+//!
+//! - Tag names and `*` -> `div`
+//! - Ids -> `#app`
+//! - Classes -> `.nav-item`
+//! - Attributes -> `[disabled]`, `[type=text]`, `[type="text"]`
+//! - `:nth-child(..)`, `:nth-last-child(..)`, `:first-child` and `:last-child`
+//! - Descendant and child combinators -> `ul li`, `ul > li`
+//! - Selector lists -> `h1, h2`
+
+use crate::VElement;
+use std::fmt;
+
+/// A parsed selector such as `ul.nav > li:nth-child(2n+1)`
+#[derive(Debug, PartialEq)]
+pub(crate) struct Selector {
+    /// The selectors in a selector list such as `h1, h2`. An element matches if any of them match.
+    alternatives: Vec<ComplexSelector>,
+}
+
+/// `ul.nav > li a`
+#[derive(Debug, PartialEq)]
+struct ComplexSelector {
+    /// The compound selectors from left to right, along with the combinator that joins each
+    /// compound selector to the one before it. The first combinator is unused.
+    compounds: Vec<(Combinator, CompoundSelector)>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Combinator {
+    /// `ul li`
+    Descendant,
+    /// `ul > li`
+    Child,
+}
+
+/// `li.active[data-id="5"]:first-child`
+#[derive(Debug, PartialEq, Default)]
+struct CompoundSelector {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, Option<String>)>,
+    nth_child: Vec<NthChild>,
+}
+
+/// Matches elements whose position among their element siblings is `a*n + b` for some n >= 0.
+#[derive(Debug, PartialEq)]
+struct NthChild {
+    a: i32,
+    b: i32,
+    /// Count positions starting from the last sibling, as in `:nth-last-child`
+    from_end: bool,
+}
+
+/// An element along with where it sits among its element siblings.
+pub(crate) struct ElementPosition<'a> {
+    pub element: &'a VElement,
+    /// 1 for the first element child of its parent, 2 for the second, etc.
+    pub position: usize,
+    /// The number of element children that the parent of this element has.
+    pub sibling_count: usize,
+}
+
+/// A selector that we could not parse.
+#[derive(Debug, PartialEq)]
+pub(crate) struct SelectorError {
+    selector: String,
+    /// Byte offset into the selector where we ran into a problem
+    pos: usize,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            r#"Invalid selector "{}" at position {}"#,
+            self.selector, self.pos
+        )
+    }
+}
+
+impl Selector {
+    /// Parse a selector such as `ul > li.active`
+    pub(crate) fn parse(selector: &str) -> Result<Selector, SelectorError> {
+        SelectorParser {
+            selector,
+            pos: 0,
+        }
+        .parse()
+    }
+
+    /// Whether or not the last element in `ancestors_and_self` matches this selector.
+    ///
+    /// `ancestors_and_self` starts with the outermost ancestor that we know about and ends with
+    /// the element that we're checking.
+    pub(crate) fn matches(&self, ancestors_and_self: &[ElementPosition]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|complex| complex.matches(&complex.compounds, ancestors_and_self))
+    }
+}
+
+impl ComplexSelector {
+    fn matches(
+        &self,
+        compounds: &[(Combinator, CompoundSelector)],
+        ancestors_and_self: &[ElementPosition],
+    ) -> bool {
+        let (combinator, compound) = match compounds.last() {
+            Some(last) => last,
+            None => return true,
+        };
+
+        let element = match ancestors_and_self.last() {
+            Some(element) => element,
+            None => return false,
+        };
+
+        if !compound.matches(element) {
+            return false;
+        }
+
+        let compounds = &compounds[..compounds.len() - 1];
+        if compounds.is_empty() {
+            return true;
+        }
+
+        let ancestors = &ancestors_and_self[..ancestors_and_self.len() - 1];
+
+        match combinator {
+            Combinator::Child => self.matches(compounds, ancestors),
+            Combinator::Descendant => {
+                (1..=ancestors.len()).any(|len| self.matches(compounds, &ancestors[..len]))
+            }
+        }
+    }
+}
+
+impl CompoundSelector {
+    fn matches(&self, element: &ElementPosition) -> bool {
+        let props = &element.element.props;
+
+        if let Some(ref tag) = self.tag {
+            if !element.element.tag.eq_ignore_ascii_case(tag) {
+                return false;
+            }
+        }
+
+        if let Some(ref id) = self.id {
+            if props.get("id") != Some(id) {
+                return false;
+            }
+        }
+
+        let classes: Vec<&str> = match props.get("class") {
+            Some(class) => class.split_whitespace().collect(),
+            None => vec![],
+        };
+        if !self.classes.iter().all(|class| classes.contains(&class.as_str())) {
+            return false;
+        }
+
+        for (name, value) in self.attributes.iter() {
+            match (props.get(name), value) {
+                (None, _) => return false,
+                (Some(actual), Some(expected)) if actual != expected => return false,
+                _ => {}
+            };
+        }
+
+        self.nth_child.iter().all(|nth| {
+            let position = if nth.from_end {
+                element.sibling_count + 1 - element.position
+            } else {
+                element.position
+            };
+            nth.matches(position)
+        })
+    }
+}
+
+impl NthChild {
+    fn matches(&self, position: usize) -> bool {
+        let position = position as i32;
+
+        if self.a == 0 {
+            return position == self.b;
+        }
+
+        let n = position - self.b;
+        n % self.a == 0 && n / self.a >= 0
+    }
+}
+
+struct SelectorParser<'a> {
+    selector: &'a str,
+    pos: usize,
+}
+
+impl<'a> SelectorParser<'a> {
+    fn parse(&mut self) -> Result<Selector, SelectorError> {
+        let mut alternatives = vec![];
+
+        loop {
+            alternatives.push(self.parse_complex()?);
+
+            if self.peek() == Some(',') {
+                self.pos += 1;
+                continue;
+            }
+
+            if self.peek().is_some() {
+                return Err(self.error());
+            }
+
+            return Ok(Selector { alternatives });
+        }
+    }
+
+    fn parse_complex(&mut self) -> Result<ComplexSelector, SelectorError> {
+        let mut compounds = vec![];
+        let mut combinator = Combinator::Descendant;
+
+        self.skip_whitespace();
+
+        loop {
+            compounds.push((combinator, self.parse_compound()?));
+
+            let had_whitespace = self.skip_whitespace();
+
+            combinator = match self.peek() {
+                Some('>') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(',') | None => return Ok(ComplexSelector { compounds }),
+                Some(_) if had_whitespace => Combinator::Descendant,
+                Some(_) => return Err(self.error()),
+            };
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<CompoundSelector, SelectorError> {
+        let mut compound = CompoundSelector::default();
+        let start = self.pos;
+
+        if self.peek() == Some('*') {
+            self.pos += 1;
+        } else if self.peek().map_or(false, is_name_char) {
+            compound.tag = Some(self.parse_name()?);
+        }
+
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.pos += 1;
+                    compound.id = Some(self.parse_name()?);
+                }
+                Some('.') => {
+                    self.pos += 1;
+                    compound.classes.push(self.parse_name()?);
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    compound.attributes.push(self.parse_attribute()?);
+                }
+                Some(':') => {
+                    self.pos += 1;
+                    compound.nth_child.push(self.parse_pseudo_class()?);
+                }
+                _ => break,
+            }
+        }
+
+        if self.pos == start {
+            return Err(self.error());
+        }
+
+        Ok(compound)
+    }
+
+    /// `data-id]` or `type="text"]`
+    fn parse_attribute(&mut self) -> Result<(String, Option<String>), SelectorError> {
+        self.skip_whitespace();
+        let name = self.parse_name()?;
+        self.skip_whitespace();
+
+        let value = match self.peek() {
+            Some('=') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                Some(self.parse_attribute_value()?)
+            }
+            _ => None,
+        };
+
+        self.skip_whitespace();
+        self.expect(']')?;
+
+        Ok((name, value))
+    }
+
+    fn parse_attribute_value(&mut self) -> Result<String, SelectorError> {
+        match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.pos += 1;
+                let rest = &self.selector[self.pos..];
+                let len = rest.find(quote).ok_or(self.error())?;
+                self.pos += len + 1;
+                Ok(rest[..len].to_string())
+            }
+            _ => self.parse_name(),
+        }
+    }
+
+    /// `nth-child(2n+1)`, `nth-last-child(2)`, `first-child` or `last-child`
+    fn parse_pseudo_class(&mut self) -> Result<NthChild, SelectorError> {
+        let start = self.pos;
+        let name = self.parse_name()?;
+
+        let from_end = match name.as_str() {
+            "first-child" => return Ok(NthChild { a: 0, b: 1, from_end: false }),
+            "last-child" => return Ok(NthChild { a: 0, b: 1, from_end: true }),
+            "nth-child" => false,
+            "nth-last-child" => true,
+            _ => {
+                return Err(SelectorError {
+                    selector: self.selector.to_string(),
+                    pos: start,
+                })
+            }
+        };
+
+        self.expect('(')?;
+        let rest = &self.selector[self.pos..];
+        let len = rest.find(')').ok_or(self.error())?;
+        let (a, b) = parse_nth(&rest[..len]).ok_or(self.error())?;
+        self.pos += len + 1;
+
+        Ok(NthChild { a, b, from_end })
+    }
+
+    fn parse_name(&mut self) -> Result<String, SelectorError> {
+        let rest = &self.selector[self.pos..];
+        let len = rest
+            .find(|c: char| !is_name_char(c))
+            .unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(self.error());
+        }
+
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SelectorError> {
+        if self.peek() != Some(c) {
+            return Err(self.error());
+        }
+        self.pos += c.len_utf8();
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.selector[self.pos..].chars().next()
+    }
+
+    /// Returns whether or not any whitespace was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let rest = &self.selector[self.pos..];
+        let trimmed = rest.trim_start();
+        self.pos += rest.len() - trimmed.len();
+        rest.len() != trimmed.len()
+    }
+
+    fn error(&self) -> SelectorError {
+        SelectorError {
+            selector: self.selector.to_string(),
+            pos: self.pos,
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// `odd`, `even`, `3`, `2n+1`, `-n+3` -> (a, b)
+fn parse_nth(nth: &str) -> Option<(i32, i32)> {
+    let nth: String = nth.chars().filter(|c| !c.is_whitespace()).collect();
+
+    match nth.as_str() {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    };
+
+    let n = match nth.find('n') {
+        Some(n) => n,
+        None => return nth.parse().ok().map(|b| (0, b)),
+    };
+
+    let a = match &nth[..n] {
+        "" | "+" => 1,
+        "-" => -1,
+        a => a.parse().ok()?,
+    };
+
+    let b = match &nth[n + 1..] {
+        "" => 0,
+        b => b.trim_start_matches('+').parse().ok()?,
+    };
+
+    Some((a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_nth_child() {
+        assert_eq!(parse_nth("odd"), Some((2, 1)));
+        assert_eq!(parse_nth("3"), Some((0, 3)));
+        assert_eq!(parse_nth("2n + 1"), Some((2, 1)));
+        assert_eq!(parse_nth("-n+3"), Some((-1, 3)));
+        assert_eq!(parse_nth("n"), Some((1, 0)));
+        assert_eq!(parse_nth("foo"), None);
+    }
+
+    #[test]
+    fn nth_child_matches() {
+        let first_three = NthChild {
+            a: -1,
+            b: 3,
+            from_end: false,
+        };
+        assert!(first_three.matches(1));
+        assert!(first_three.matches(3));
+        assert!(!first_three.matches(4));
+
+        let odd = NthChild {
+            a: 2,
+            b: 1,
+            from_end: false,
+        };
+        assert!(odd.matches(1));
+        assert!(!odd.matches(2));
+        assert!(odd.matches(5));
+    }
+
+    #[test]
+    fn invalid_selectors() {
+        assert!(Selector::parse("").is_err());
+        assert!(Selector::parse("div >").is_err());
+        assert!(Selector::parse("[id").is_err());
+        assert!(Selector::parse(":hover").is_err());
+        assert!(Selector::parse("div, , span").is_err());
+    }
+}
@@ -0,0 +1,400 @@
+//! A collection of functions that are useful for unit testing your html! views.
+
+use crate::selector::{ElementPosition, Selector};
+use crate::{VirtualNode, VElement};
+
+mod snapshot;
+pub use self::snapshot::{assert_snapshot_matches, UPDATE_SNAPSHOTS_VAR};
+
+impl VirtualNode {
+    /// Get a vector of all of the VirtualNode children / grandchildren / etc of
+    /// your virtual_node that have a label that matches your filter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// # #[macro_use] extern crate virtual_dom_rs;  fn main() {
+    ///
+    /// let component = html! {<div>
+    ///  <span label="hello",> {"Hi!"} </span>
+    ///  <em label="world",> {"There!!"} </em>
+    ///  <em label="hello",></em>
+    /// </div> };
+    ///
+    /// let hello_nodes = component.filter_label(|label| {
+    ///     label.contains("hello")
+    /// });
+    ///
+    /// assert_eq!(hello_nodes.len(), 2);
+    /// }
+    /// ```
+    pub fn filter_label<'a, F>(&'a self, filter: F) -> Vec<&'a VirtualNode>
+    where
+        F: Fn(&str) -> bool,
+    {
+        // Get descendants recursively
+        let mut descendants: Vec<&'a VirtualNode> = vec![];
+        match self {
+            VirtualNode::Text(_) => { /* nothing to do */ },
+            VirtualNode::Element(element_node) => {
+                for child in element_node.children.iter() {
+                    get_descendants(&mut descendants, child);
+                }
+            },
+        }
+
+        // Filter descendants
+        descendants
+            .into_iter()
+            .filter(|vn: &&'a VirtualNode| {
+                match vn {
+                    VirtualNode::Text(_) => false,
+                    VirtualNode::Element(element_node) => {
+                        match element_node.props.get("label") {
+                            Some(label) => filter(label),
+                            None => false,
+                        }
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Get a vector of all of the descendants of this VirtualNode
+    /// that have the provided `filter`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// # #[macro_use] extern crate virtual_dom_rs;  fn main() {
+    ///
+    /// let component = html! {<div>
+    ///  <span label="hello",> {"Hi!"} </span>
+    ///  <em label="world",> {"There!!"} </em>
+    ///  <em label="hello",></em>
+    /// </div> };
+    ///
+    /// let hello_nodes = component.filter_label_equals("hello");
+    ///
+    /// assert_eq!(hello_nodes.len(), 2);
+    /// }
+    /// ```
+    pub fn filter_label_equals<'a>(&'a self, label: &str) -> Vec<&'a VirtualNode> {
+        self.filter_label(|node_label| node_label == label)
+    }
+
+    /// Get the first descendant of this VirtualNode that matches a CSS selector.
+    ///
+    /// Supports tags, `#id`, `.class`, `[attr]`, `[attr=value]`, `:nth-child(..)`,
+    /// `:nth-last-child(..)`, `:first-child`, `:last-child`, descendant (`ul li`) and
+    /// child (`ul > li`) combinators and selector lists (`h1, h2`).
+    ///
+    /// # Panics
+    ///
+    /// If the selector is invalid or uses something that we don't support.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// # #[macro_use] extern crate virtual_dom_rs;  fn main() {
+    ///
+    /// let component = html! {<div>
+    ///  <ul class="nav">
+    ///    <li> Home </li>
+    ///    <li class="active"> Contributors </li>
+    ///  </ul>
+    /// </div> };
+    ///
+    /// let active = component.query_selector("ul.nav > li.active").unwrap();
+    ///
+    /// assert_eq!(component.query_selector("ul > li:nth-child(2)"), Some(active));
+    /// }
+    /// ```
+    pub fn query_selector<'a>(&'a self, selector: &str) -> Option<&'a VirtualNode> {
+        self.query_selector_all(selector).into_iter().next()
+    }
+
+    /// Get all of the descendants of this VirtualNode that match a CSS selector, in the same
+    /// order that they appear in the tree.
+    ///
+    /// See [`query_selector`] for the selectors that are supported.
+    ///
+    /// # Panics
+    ///
+    /// If the selector is invalid or uses something that we don't support.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// # #[macro_use] extern crate virtual_dom_rs;  fn main() {
+    ///
+    /// let component = html! {<div>
+    ///  <span class="greeting"> Hi! </span>
+    ///  <em class="greeting"> There!! </em>
+    ///  <em></em>
+    /// </div> };
+    ///
+    /// assert_eq!(component.query_selector_all(".greeting").len(), 2);
+    /// assert_eq!(component.query_selector_all("div > em").len(), 2);
+    /// }
+    /// ```
+    ///
+    /// [`query_selector`]: #method.query_selector
+    pub fn query_selector_all<'a>(&'a self, selector: &str) -> Vec<&'a VirtualNode> {
+        let selector = match Selector::parse(selector) {
+            Ok(selector) => selector,
+            Err(err) => panic!("{}", err),
+        };
+
+        let mut matches = vec![];
+
+        if let VirtualNode::Element(element_node) = self {
+            let mut ancestors = vec![ElementPosition {
+                element: element_node,
+                position: 1,
+                sibling_count: 1,
+            }];
+
+            get_selector_matches(&selector, element_node, &mut ancestors, &mut matches);
+        }
+
+        matches
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl VirtualNode {
+    /// Call this element's handler for an event, such as `"click"` or `"oninput"`, with a
+    /// simulated event. This lets you unit test what your views do when they are interacted
+    /// with without needing to run in a browser.
+    ///
+    /// The handler receives `event` as is, so it must be of the type that the closure in your
+    /// `html!` view accepts. For example, a handler written as `oninput=|value: String| {..}`
+    /// can be triggered with the text that was typed into the input.
+    ///
+    /// # Panics
+    ///
+    /// If this node is not an element, the element does not have a handler for the event
+    /// or the handler expects a different type of event.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// # #[macro_use] extern crate virtual_dom_rs;  fn main() {
+    ///
+    /// let count = Rc::new(Cell::new(0));
+    /// let count_clone = Rc::clone(&count);
+    ///
+    /// let component = html! {<div>
+    ///  <button onclick=move |_: u8| { count_clone.set(count_clone.get() + 1) }>
+    ///    Increment
+    ///  </button>
+    /// </div> };
+    ///
+    /// component.query_selector("button").unwrap().trigger_event("click", 0_u8);
+    ///
+    /// assert_eq!(count.get(), 1);
+    /// }
+    /// ```
+    pub fn trigger_event<E: 'static>(&self, event_name: &str, event: E) {
+        let element_node = match self {
+            VirtualNode::Element(element_node) => element_node,
+            VirtualNode::Text(_) => panic!("Cannot trigger {} on a text node", event_name),
+        };
+
+        // click -> onclick
+        let onevent = if event_name.starts_with("on") {
+            event_name.to_string()
+        } else {
+            format!("on{}", event_name)
+        };
+
+        let handler = match element_node.events.0.get(&onevent) {
+            Some(handler) => handler,
+            None => panic!("<{}> does not have an {} handler", element_node.tag, onevent),
+        };
+
+        (&mut *handler.borrow_mut())(Box::new(event));
+    }
+}
+
+/// Push all of the descendants of `parent` that match our selector onto `matches`.
+///
+/// `ancestors` holds `parent` as well as all of its ancestors so that we can match
+/// selectors such as `ul > li`.
+fn get_selector_matches<'a>(
+    selector: &Selector,
+    parent: &'a VElement,
+    ancestors: &mut Vec<ElementPosition<'a>>,
+    matches: &mut Vec<&'a VirtualNode>,
+) {
+    let sibling_count = parent
+        .children
+        .iter()
+        .filter(|child| child.as_velement_ref().is_some())
+        .count();
+
+    let mut position = 0;
+
+    for child in parent.children.iter() {
+        if let VirtualNode::Element(element_node) = child {
+            position += 1;
+
+            ancestors.push(ElementPosition {
+                element: element_node,
+                position,
+                sibling_count,
+            });
+
+            if selector.matches(ancestors) {
+                matches.push(child);
+            }
+
+            get_selector_matches(selector, element_node, ancestors, matches);
+
+            ancestors.pop();
+        }
+    }
+}
+
+fn get_descendants<'a>(descendants: &mut Vec<&'a VirtualNode>, node: &'a VirtualNode) {
+    descendants.push(node);
+    match node {
+        VirtualNode::Text(_) => { /* nothing to do */ },
+        VirtualNode::Element(element_node) => {
+            for child in element_node.children.iter() {
+                get_descendants(descendants, child);
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // TODO: Move this test somewhere that we can use the `html!` macro
+    //    #[test]
+    //    fn filter_label() {
+    //        let html = html! {
+    //        // Should not pick up labels on the root node
+    //        <div label="hello0",>
+    //            // This node gets picked up
+    //            <span label="hello1",>
+    //            </span>
+    //            // This node gets picked up
+    //            <em label="hello2",>
+    //                { "hello there :)!" }
+    //            </em>
+    //            <div label="world",></div>
+    //        </div>
+    //        };
+    //
+    //        let hello_nodes = html.filter_label(|label| label.contains("hello"));
+    //
+    //        assert_eq!(
+    //            hello_nodes.len(),
+    //            2,
+    //            "2 elements with label containing 'hello'"
+    //        );
+    //    }
+
+    #[test]
+    fn label_equals() {
+        let span = VirtualNode::element("span");
+
+        let mut props = HashMap::new();
+        props.insert("label".to_string(), "hello".to_string());
+        let mut em = VElement::new("em");
+        em.props = props;
+
+        let mut html = VElement::new("div");
+        html.children.push(span);
+        html.children.push(em.into());
+
+        let html_node = VirtualNode::Element(html);
+        let hello_nodes = html_node.filter_label_equals("hello");
+
+        assert_eq!(hello_nodes.len(), 1);
+    }
+
+    #[test]
+    fn query_selector() {
+        let html = VirtualNode::from_html(
+            r#"
+        <div id="app">
+            <ul class="nav main">
+                <li class="item"><a href="/">Home</a></li>
+                <li class="item active"><a href="/contributors">Contributors</a></li>
+                <li class="item"><span><a href="/about" data-external>About</a></span></li>
+            </ul>
+            <input type="text">
+        </div>
+        "#,
+        )
+        .unwrap();
+
+        let links = html.query_selector_all("a");
+        assert_eq!(links.len(), 3);
+
+        let active = html.query_selector("ul.nav li.item.active > a").unwrap();
+        assert_eq!(active.as_velement_ref().unwrap().props["href"], "/contributors");
+        assert_eq!(html.query_selector("#app li:nth-child(2) a"), Some(active));
+
+        assert_eq!(html.query_selector_all("li > a").len(), 2);
+        assert_eq!(html.query_selector_all("ul a").len(), 3);
+        assert_eq!(html.query_selector_all("li:nth-child(odd)").len(), 2);
+        assert_eq!(html.query_selector_all("li:first-child, li:last-child").len(), 2);
+        assert_eq!(html.query_selector_all("[data-external]").len(), 1);
+        assert_eq!(html.query_selector_all(r#"input[type="text"]"#).len(), 1);
+        assert_eq!(html.query_selector_all("input[type=password]").len(), 0);
+        assert_eq!(html.query_selector_all("*").len(), 9);
+        assert!(html.query_selector("section").is_none());
+    }
+
+    #[test]
+    fn trigger_event() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let typed = Rc::new(RefCell::new(vec![]));
+        let typed_clone = Rc::clone(&typed);
+
+        let mut input = VElement::new("input");
+        input
+            .events
+            .insert_handler("oninput", move |value: String| typed_clone.borrow_mut().push(value));
+
+        let mut html = VElement::new("div");
+        html.children.push(input.into());
+        let html = VirtualNode::Element(html);
+
+        let input = html.query_selector("input").unwrap();
+        input.trigger_event("input", "h".to_string());
+        input.trigger_event("oninput", "hi".to_string());
+
+        assert_eq!(*typed.borrow(), vec!["h".to_string(), "hi".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "expects an event of type")]
+    fn trigger_event_wrong_type() {
+        let mut button = VElement::new("button");
+        button.events.insert_handler("onclick", |_: u8| {});
+
+        VirtualNode::Element(button).trigger_event("click", "not a u8");
+    }
+
+    #[test]
+    #[should_panic(expected = "does not have an onclick handler")]
+    fn trigger_missing_event() {
+        VirtualNode::element("button").trigger_event("click", 0_u8);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid selector")]
+    fn invalid_selector() {
+        VirtualNode::element("div").query_selector("ul >");
+    }
+}
@@ -0,0 +1,199 @@
+//! Compare the HTML that your views render against snapshots that are stored next to your
+//! tests.
+
+use crate::{RenderOptions, VirtualNode};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Set this environment variable (i.e. `UPDATE_SNAPSHOTS=1 cargo test`) to write what your
+/// views currently render to their snapshot files instead of comparing against them. This is
+/// also how new snapshots get created.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Assert that a `VirtualNode` renders the same HTML as the snapshot with the given name.
+///
+/// Snapshots live in a `snapshots` directory next to your crate's `Cargo.toml`, so
+/// `assert_snapshot!("home_view", node)` compares against `snapshots/home_view.snap`.
+/// A snapshot that doesn't exist yet is a failure, so that a snapshot that was never
+/// committed can't silently pass in CI. Create it by running your tests with
+/// `UPDATE_SNAPSHOTS=1`.
+///
+/// For a `View`, snapshot what it renders with `assert_snapshot!("home_view", view.render())`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// # #[macro_use] extern crate virtual_dom_rs;  fn main() {
+///
+/// let component = html! {<div id="app">
+///  <h1> Hello World </h1>
+/// </div> };
+///
+/// assert_snapshot!("hello_world", component);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $node:expr) => {
+        $crate::virtual_node_test_utils::assert_snapshot_matches(
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("snapshots")
+                .join(format!("{}.snap", $name)),
+            &$node,
+        )
+    };
+}
+
+/// Assert that a `VirtualNode` renders the same HTML as the snapshot file at `snapshot_path`.
+///
+/// You'll usually want to use the `assert_snapshot!` macro instead of calling this directly.
+///
+/// # Panics
+///
+/// If the rendered HTML differs from the snapshot, or the snapshot doesn't exist and we aren't
+/// updating snapshots. The panic message contains a line by line diff of the two.
+pub fn assert_snapshot_matches<P: AsRef<Path>>(snapshot_path: P, node: &VirtualNode) {
+    let update = env::var_os(UPDATE_SNAPSHOTS_VAR).is_some();
+
+    if let Err(message) = check_snapshot(snapshot_path.as_ref(), &pretty_html(node), update) {
+        panic!("{}", message);
+    }
+}
+
+/// Compare our rendered HTML against the snapshot, writing the snapshot instead if we're
+/// updating snapshots.
+fn check_snapshot(snapshot_path: &Path, rendered: &str, update: bool) -> Result<(), String> {
+    if update {
+        if let Some(dir) = snapshot_path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("Could not create {:?}: {}", dir, err))?;
+        }
+
+        return fs::write(snapshot_path, rendered)
+            .map_err(|err| format!("Could not write snapshot {:?}: {}", snapshot_path, err));
+    }
+
+    if !snapshot_path.exists() {
+        return Err(format!(
+            "Snapshot {:?} does not exist. Run your tests with {}=1 to create it.",
+            snapshot_path, UPDATE_SNAPSHOTS_VAR
+        ));
+    }
+
+    let snapshot = fs::read_to_string(snapshot_path)
+        .map_err(|err| format!("Could not read snapshot {:?}: {}", snapshot_path, err))?;
+    // In case git checked the snapshot out with Windows line endings
+    let snapshot = snapshot.replace("\r\n", "\n");
+
+    if snapshot == rendered {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Rendered HTML does not match snapshot {:?}\n\n--- snapshot\n+++ rendered\n{}\nRun your tests with {}=1 to update the snapshot.",
+        snapshot_path,
+        line_diff(&snapshot, rendered),
+        UPDATE_SNAPSHOTS_VAR
+    ))
+}
+
+/// Render a node as indented HTML with attributes sorted by name, so that snapshots are
+/// stable and diff nicely.
+fn pretty_html(node: &VirtualNode) -> String {
+    node.to_html(&RenderOptions {
+        sort_attributes: true,
+        ..RenderOptions::pretty()
+    })
+}
+
+/// A line by line diff of `expected` and `actual`, based on their longest common subsequence.
+///
+/// Lines only in `expected` are prefixed with `-`, lines only in `actual` with `+`.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of expected[i..] and actual[j..]
+    let mut lcs = vec![vec![0; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff.push_str(&format!("  {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("- {}\n", expected[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+ {}\n", actual[j]));
+            j += 1;
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pretty_html_indents_and_sorts_attributes() {
+        let node = VirtualNode::from_html(
+            r#"<div id="app" class="main"><h1>Hello<em>World</em></h1><br><p>a &lt; b</p><span></span></div>"#,
+        )
+        .unwrap();
+
+        let expected = r#"<div class="main" id="app">
+  <h1>Hello<em>World</em></h1>
+  <br>
+  <p>a &lt; b</p>
+  <span></span>
+</div>
+"#;
+
+        assert_eq!(pretty_html(&node), expected);
+    }
+
+    #[test]
+    fn line_diff_marks_changed_lines() {
+        let diff = line_diff("<div>\n  <h1>Hi</h1>\n</div>", "<div>\n  <h2>Hi</h2>\n</div>");
+
+        assert_eq!(diff, "  <div>\n-   <h1>Hi</h1>\n+   <h2>Hi</h2>\n  </div>\n");
+    }
+
+    #[test]
+    fn check_snapshot_creates_compares_and_updates() {
+        let dir = env::temp_dir().join(format!("virtual-node-snapshots-{}", std::process::id()));
+        let snapshot = dir.join("view.snap");
+
+        let err = check_snapshot(&snapshot, "<div></div>\n", false).unwrap_err();
+        assert!(err.contains("does not exist"), "{}", err);
+        assert!(!snapshot.exists());
+
+        check_snapshot(&snapshot, "<div></div>\n", true).unwrap();
+        assert_eq!(fs::read_to_string(&snapshot).unwrap(), "<div></div>\n");
+
+        assert!(check_snapshot(&snapshot, "<div></div>\n", false).is_ok());
+
+        let err = check_snapshot(&snapshot, "<span></span>\n", false).unwrap_err();
+        assert!(err.contains("- <div></div>\n+ <span></span>\n"), "{}", err);
+
+        check_snapshot(&snapshot, "<span></span>\n", true).unwrap();
+        assert!(check_snapshot(&snapshot, "<span></span>\n", false).is_ok());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -16,6 +16,10 @@ static SOME_COMPONENT_CSS: &'static str = css! {"
 }
 "};
 
+mod theme {
+    pub const PRIMARY: &'static str = "#2a38ef";
+}
+
 fn main() {
     let listener = TcpListener::bind("127.0.0.1:22217").unwrap();
 
@@ -57,10 +61,10 @@ fn render_app() -> virtual_dom_rs::VirtualNode {
     :host {
         display: flex;
         flex-direction: column;
-    }
 
-    :host > h3 {
-        color: blue;
+        > h3 {
+            color: blue;
+        }
     }
 
     .red {
@@ -70,16 +74,47 @@ fn render_app() -> virtual_dom_rs::VirtualNode {
 
     let another_component_css = &format!("{} more classes can go here", another_component_css);
 
+    // With `module` every class is local and we get a struct with a field for each one
+    let list_classes = css! {module r#"
+    .list {
+        list-style: none;
+    }
+
+    .list-item {
+        animation: fade-in 1s;
+    }
+
+    @keyframes fade-in {
+        from { opacity: 0; }
+    }
+    "#};
+
     let some_component = html! {
     <h1 class=SOME_COMPONENT_CSS>
         And there we have it
     </h1>
     };
 
+    // Rust values become custom properties that get set through the style attribute
+    let border_width = 2;
+    let themed = css! {r#"
+    :host {
+        color: {theme::PRIMARY};
+        border: solid {theme::PRIMARY} {border_width}px;
+    }
+    "#};
+
+    let themed_component = html! {
+    <p class=themed.class style=themed.style> Even Rust values </p>
+    };
+
     let another_component = html! {
     <div class=another_component_css>
         <h3> we have some </h3>
         <span class="red"> CSS </span>
+        <ul class=list_classes.list>
+            <li class=list_classes.list_item> And local classes </li>
+        </ul>
     </div>
     };
 
@@ -87,6 +122,7 @@ fn render_app() -> virtual_dom_rs::VirtualNode {
       <div>
        {some_component}
        {another_component}
+       {themed_component}
        <link rel="stylesheet" type="text/css" href="/app.css" />
      </div>
     }
@@ -25,7 +25,6 @@ pub use crate::store::*;
 mod state;
 pub use crate::state::*;
 use crate::views::*;
-use std::collections::HashMap;
 
 mod views;
 
@@ -56,42 +55,63 @@ impl App {
 }
 
 impl App {
+    /// What to show for the store's current path. The server uses this to decide on a
+    /// response's status code.
+    pub fn resolve(&self) -> Resolved {
+        self.router.resolve(self.store.borrow().path())
+    }
+
     pub fn render(&self) -> VirtualNode {
-        #[allow(unused_variables)] // Compiler doesn't see it inside html macro
-        let store = Rc::clone(&self.store);
+        let mut path = self.store.borrow().path().to_string();
+
+        // Render the page that we'll end up on after following any redirects
+        for _ in 0..MAX_REDIRECTS {
+            match self.router.resolve(&path) {
+                Resolved::View(view) | Resolved::NotFound(view) => return view.render(),
+                Resolved::Redirect(redirect) => path = redirect.path,
+            }
+        }
 
-        self.router
-            .view(self.store.borrow().path())
-            .unwrap()
-            .render()
+        panic!("Too many redirects from {}", self.store.borrow().path());
     }
 }
 
+const MAX_REDIRECTS: usize = 10;
+
 fn make_router(store: Rc<RefCell<Store>>) -> Router {
-    let mut router = Router::default();
+    let mut router = Router::new(create_routes![home_route, contributors_route]);
 
-    let store_clone = Rc::clone(&store);
+    let nav_links = NavLinks {
+        home: router.url_for("home", &[]).unwrap(),
+        contributors: router.url_for("contributors", &[]).unwrap(),
+    };
 
-    let param_types = HashMap::new();
-    let home_route = Route::new(
-        "/",
-        param_types,
-        Box::new(move |params| Box::new(HomeView::new(Rc::clone(&store_clone))) as Box<View>),
-    );
+    router.set_not_found(Box::new(|params: Params| {
+        let store = params.provided::<RefCell<Store>>().unwrap();
+        let nav_links = params.provided::<NavLinks>().unwrap();
 
-    router.add_route(home_route);
+        Box::new(NotFoundView::new(Rc::clone(&store), Rc::clone(&nav_links))) as Box<View>
+    }));
 
-    let param_types = HashMap::new();
-    let contributors_route = Route::new(
-        "/contributors",
-        param_types,
-        Box::new(move |params| Box::new(ContributorsView::new(Rc::clone(&store))) as Box<View>),
-    );
-    router.add_route(contributors_route);
+    router.provide(store);
+    router.provide(Rc::new(nav_links));
 
     router
 }
 
+#[route(path = "/", name = "home")]
+fn home_route(store: Provided<RefCell<Store>>, nav_links: Provided<NavLinks>) -> VirtualNode {
+    HomeView::new(Rc::clone(&store), Rc::clone(&nav_links)).render()
+}
+
+#[route(path = "/contributors", name = "contributors")]
+fn contributors_route(
+    store: Provided<RefCell<Store>>,
+    nav_links: Provided<NavLinks>,
+) -> VirtualNode {
+    ContributorsView::new(Rc::clone(&store), Rc::clone(&nav_links)).render()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,7 +121,40 @@ mod tests {
         let app = App::new(5);
 
         assert_eq!(app.store.borrow().click_count(), 5);
-        app.store.borrow_mut().msg(&Msg::Click);
+        app.store.borrow_mut().dispatch(Msg::Click);
         assert_eq!(app.store.borrow().click_count(), 6);
     }
+
+    #[test]
+    fn nav_links_match_routes() {
+        let store = Rc::new(RefCell::new(Store::new(State::new(0))));
+        let router = make_router(store);
+
+        assert_eq!(router.url_for("home", &[]), Ok("/".to_string()));
+        assert_eq!(
+            router.url_for("contributors", &[]),
+            Ok("/contributors".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_paths_are_not_found() {
+        let app = App::new(0);
+        app.store
+            .borrow_mut()
+            .dispatch(Msg::Path("/not-a-page".to_string()));
+
+        match app.resolve() {
+            Resolved::NotFound(_) => {}
+            _ => panic!("Should not have found /not-a-page"),
+        }
+        assert!(app.render().to_string().contains("Page not found"));
+    }
+
+    #[test]
+    fn every_route_is_reachable() {
+        let store = Rc::new(RefCell::new(Store::new(State::new(0))));
+
+        assert_eq!(make_router(store).unreachable_routes(), Vec::<String>::new());
+    }
 }
@@ -8,8 +8,6 @@ pub use self::msg::Msg;
 #[derive(Serialize, Deserialize)]
 pub struct State {
     click_count: Rc<Cell<u32>>,
-    #[serde(skip)]
-    listeners: Vec<Box<Fn() -> ()>>,
     path: String,
 }
 
@@ -18,9 +16,6 @@ impl State {
         State {
             path: "/".to_string(),
             click_count: Rc::new(Cell::new(count)),
-            // TODO: Move this to the store.. shouldn't be storing functions in state
-            // just data
-            listeners: vec![],
         }
     }
 
@@ -35,26 +30,18 @@ impl State {
     }
 }
 
-impl State {
-    pub fn subscribe(&mut self, callback: Box<Fn() -> ()>) {
-        self.listeners.push(callback)
-    }
-}
+impl store_rs::State for State {
+    type Msg = Msg;
 
-impl State {
-    pub fn msg(&mut self, msg: &Msg) {
+    fn msg(&mut self, msg: &Msg) {
         match msg {
             Msg::Click => self.increment_click(),
             Msg::Path(path) => self.set_path(path.to_string()),
         };
-
-        // Whenever we update state we'll let all of our state listeners know that state was
-        // updated
-        for callback in self.listeners.iter() {
-            callback();
-        }
     }
+}
 
+impl State {
     pub fn click_count(&self) -> u32 {
         self.click_count.get()
     }
@@ -1,23 +1,30 @@
 use crate::store::Store;
 use crate::views::nav_bar_view::ActivePage;
 use crate::views::nav_bar_view::NavBarView;
+use crate::views::nav_bar_view::NavLinks;
 use std::cell::RefCell;
 use std::rc::Rc;
 use virtual_dom_rs::prelude::*;
 
 pub struct ContributorsView {
     store: Rc<RefCell<Store>>,
+    nav_links: Rc<NavLinks>,
 }
 
 impl ContributorsView {
-    pub fn new(store: Rc<RefCell<Store>>) -> ContributorsView {
-        ContributorsView { store }
+    pub fn new(store: Rc<RefCell<Store>>, nav_links: Rc<NavLinks>) -> ContributorsView {
+        ContributorsView { store, nav_links }
     }
 }
 
 impl View for ContributorsView {
     fn render(&self) -> VirtualNode {
-        let nav_bar = NavBarView::new(ActivePage::Contributors, Rc::clone(&self.store)).render();
+        let nav_bar = NavBarView::new(
+            ActivePage::Contributors,
+            Rc::clone(&self.store),
+            Rc::clone(&self.nav_links),
+        )
+        .render();
 
         html! {
         <div>
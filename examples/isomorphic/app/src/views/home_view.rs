@@ -1,6 +1,7 @@
 use crate::store::Store;
 use crate::views::nav_bar_view::ActivePage;
 use crate::views::nav_bar_view::NavBarView;
+use crate::views::nav_bar_view::NavLinks;
 use crate::Msg;
 
 use virtual_dom_rs::prelude::*;
@@ -10,17 +11,23 @@ use std::rc::Rc;
 
 pub struct HomeView {
     store: Rc<RefCell<Store>>,
+    nav_links: Rc<NavLinks>,
 }
 
 impl HomeView {
-    pub fn new(store: Rc<RefCell<Store>>) -> HomeView {
-        HomeView { store }
+    pub fn new(store: Rc<RefCell<Store>>, nav_links: Rc<NavLinks>) -> HomeView {
+        HomeView { store, nav_links }
     }
 }
 
 impl View for HomeView {
     fn render(&self) -> VirtualNode {
-        let nav_bar = NavBarView::new(ActivePage::Home, Rc::clone(&self.store)).render();
+        let nav_bar = NavBarView::new(
+            ActivePage::Home,
+            Rc::clone(&self.store),
+            Rc::clone(&self.nav_links),
+        )
+        .render();
 
         let store = Rc::clone(&self.store);
 
@@ -36,7 +43,7 @@ impl View for HomeView {
           { nav_bar }
 
           <span> The button has been clicked: { click_component } times!</span>
-          <button onclick=move|_: u8| { store.borrow_mut().msg(&Msg::Click) }>
+          <button onclick=move|_: u8| { store.borrow_mut().dispatch(Msg::Click) }>
             Click me!
           </button>
           <div> In this time Ferris has made { text!(click_count) } new friends. </div>
@@ -6,3 +6,6 @@ pub use self::nav_bar_view::*;
 
 pub mod contributors_view;
 pub use self::contributors_view::*;
+
+pub mod not_found_view;
+pub use self::not_found_view::*;
@@ -11,27 +11,46 @@ use self::nav_bar_item_view::NavBarItemView;
 pub struct NavBarView {
     active_page: ActivePage,
     store: Rc<RefCell<Store>>,
+    nav_links: Rc<NavLinks>,
 }
 
 impl NavBarView {
-    pub fn new(active_page: ActivePage, store: Rc<RefCell<Store>>) -> NavBarView {
-        NavBarView { active_page, store }
+    pub fn new(
+        active_page: ActivePage,
+        store: Rc<RefCell<Store>>,
+        nav_links: Rc<NavLinks>,
+    ) -> NavBarView {
+        NavBarView {
+            active_page,
+            store,
+            nav_links,
+        }
     }
 }
 
+/// The paths of the pages in the nav bar, created from our routes with `Router::url_for`
+pub struct NavLinks {
+    pub home: String,
+    pub contributors: String,
+}
+
 pub enum ActivePage {
     Home,
     Contributors,
+    NotFound,
 }
 
 impl View for NavBarView {
     fn render(&self) -> VirtualNode {
         let store = self.store.borrow();
 
-        let home = NavBarItemView::new(Rc::clone(&self.store), "/", "Isomorphic Web App", "");
+        let home = NavBarItemView::new(
+            self.nav_links.home.clone(),
+            "Isomorphic Web App",
+            "",
+        );
         let contributors = NavBarItemView::new(
-            Rc::clone(&self.store),
-            "/contributors",
+            self.nav_links.contributors.clone(),
             "Contributors",
             "margin-left: auto;",
         );
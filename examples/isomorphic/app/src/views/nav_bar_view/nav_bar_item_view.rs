@@ -1,51 +1,28 @@
-use crate::store::Store;
-use crate::Msg;
 use css_rs_macro::css;
-use std::cell::RefCell;
-use std::rc::Rc;
 use virtual_dom_rs::prelude::*;
 
+/// A link to one of our pages. In the browser `router_rs::History` handles clicks on it
+/// without reloading the page.
 pub struct NavBarItemView {
-    path: &'static str,
+    path: String,
     text: &'static str,
     style: &'static str,
-    store: Rc<RefCell<Store>>,
 }
 
 impl NavBarItemView {
-    pub fn new(
-        store: Rc<RefCell<Store>>,
-        path: &'static str,
-        text: &'static str,
-        style: &'static str,
-    ) -> NavBarItemView {
-        NavBarItemView {
-            store,
-            path,
-            text,
-            style,
-        }
+    pub fn new(path: String, text: &'static str, style: &'static str) -> NavBarItemView {
+        NavBarItemView { path, text, style }
     }
 }
 
 impl View for NavBarItemView {
     fn render(&self) -> VirtualNode {
-        let store = Rc::clone(&self.store);
-
-        let path = self.path;
-
         let text = VirtualNode::text(self.text);
 
         html! {
-            <span
-                style=self.style
-                class=NAV_BAR_ITEM_CSS
-                onclick=move |_ev: u32| {
-                  store.borrow_mut().msg(&Msg::Path(path.to_string()));
-                }
-            >
+            <a href=self.path style=self.style class=NAV_BAR_ITEM_CSS>
               { text }
-            </span>
+            </a>
         }
     }
 }
@@ -53,10 +30,12 @@ impl View for NavBarItemView {
 static NAV_BAR_ITEM_CSS: &'static str = css! {"
 :host {
     border-bottom: solid transparent 3px;
+    color: inherit;
     cursor: pointer;
-}
+    text-decoration: none;
 
-:host:hover {
-    border-bottom: solid white 3px;
+    &:hover {
+        border-bottom: solid white 3px;
+    }
 }
 "};
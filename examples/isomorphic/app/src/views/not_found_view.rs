@@ -0,0 +1,38 @@
+use crate::store::Store;
+use crate::views::nav_bar_view::ActivePage;
+use crate::views::nav_bar_view::NavBarView;
+use crate::views::nav_bar_view::NavLinks;
+use std::cell::RefCell;
+use std::rc::Rc;
+use virtual_dom_rs::prelude::*;
+
+pub struct NotFoundView {
+    store: Rc<RefCell<Store>>,
+    nav_links: Rc<NavLinks>,
+}
+
+impl NotFoundView {
+    pub fn new(store: Rc<RefCell<Store>>, nav_links: Rc<NavLinks>) -> NotFoundView {
+        NotFoundView { store, nav_links }
+    }
+}
+
+impl View for NotFoundView {
+    fn render(&self) -> VirtualNode {
+        let nav_bar = NavBarView::new(
+            ActivePage::NotFound,
+            Rc::clone(&self.store),
+            Rc::clone(&self.nav_links),
+        )
+        .render();
+
+        html! {
+        <div>
+            { nav_bar }
+            <div>
+             Page not found
+            </div>
+        </div>
+        }
+    }
+}
@@ -5,31 +5,20 @@ use console_error_panic_hook;
 use virtual_dom_rs::prelude::*;
 
 use web_sys;
-use web_sys::Element;
 
 use isomorphic_app;
 use isomorphic_app::App;
-use isomorphic_app::VirtualNode;
+use isomorphic_app::Msg;
+use isomorphic_app::State;
 
-#[wasm_bindgen]
-pub struct Client {
-    app: App,
-    dom_updater: DomUpdater,
-}
+use router_rs::{History, HistoryMode, Resolved};
 
-// Expose globals from JS for things such as request animation frame
-// that web sys doesn't seem to have yet
-//
-// TODO: Remove this and use RAF from Rust
-// https://rustwasm.github.io/wasm-bindgen/api/web_sys/struct.Window.html#method.request_animation_frame
-#[wasm_bindgen]
-extern "C" {
-    pub type GlobalJS;
+use std::rc::Rc;
 
-    pub static global_js: GlobalJS;
-
-    #[wasm_bindgen(method)]
-    pub fn update(this: &GlobalJS);
+#[wasm_bindgen]
+pub struct Client {
+    // Listens for link clicks and the back button for as long as the client is alive
+    history: Rc<History>,
 }
 
 #[wasm_bindgen]
@@ -40,25 +29,54 @@ impl Client {
 
         let app = App::from_state_json(initial_state);
 
-        // TODO: Use request animation frame from web_sys
-        // https://rustwasm.github.io/wasm-bindgen/api/web_sys/struct.Window.html#method.request_animation_frame
-        app.store.borrow_mut().subscribe(Box::new(|| {
-            web_sys::console::log_1(&JsValue::from("Updating state"));
-            global_js.update();
-        }));
+        let store = Rc::clone(&app.store);
+        let history = Rc::new(History::new(
+            HistoryMode::Path,
+            Box::new(move |path: &str| store.borrow_mut().dispatch(Msg::Path(path.to_string()))),
+        ));
+
+        // The server renders the page for the path that was requested, but keep the store in
+        // sync in case they differ
+        let path = history.current_path();
+        if app.store.borrow().path() != path {
+            app.store.borrow_mut().dispatch(Msg::Path(path));
+        }
 
         let window = web_sys::window().unwrap();
         let document = window.document().unwrap();
         let root_node = document
             .get_element_by_id("isomorphic-rust-web-app")
             .unwrap();
-        let dom_updater = DomUpdater::new_replace_mount(app.render(), root_node);
+        let mut dom_updater = DomUpdater::new_replace_mount(app.render(), root_node);
+
+        let store = Rc::clone(&app.store);
+        let scheduler = {
+            let history = Rc::downgrade(&history);
+
+            RenderScheduler::new(move || {
+                // Show the redirect's path in the address bar. This updates the store's path,
+                // which schedules another render.
+                if let (Resolved::Redirect(redirect), Some(history)) =
+                    (app.resolve(), history.upgrade())
+                {
+                    history.replace(&redirect.path);
+                }
+
+                dom_updater.update(app.render());
+            })
+        };
+
+        // However many messages get dispatched we only re-render once per animation frame
+        store.borrow_mut().subscribe(move |_: &State| {
+            web_sys::console::log_1(&JsValue::from("Updating state"));
+            scheduler.schedule();
+        });
 
-        Client { app, dom_updater }
+        Client { history }
     }
 
-    pub fn render(&mut self) {
-        let vdom = self.app.render();
-        self.dom_updater.update(vdom);
+    /// Navigate to a path from JavaScript, i.e. `client.navigate('/contributors')`
+    pub fn navigate(&self, path: &str) {
+        self.history.navigate(path);
     }
 }
@@ -1,4 +1,5 @@
 extern crate isomorphic_app;
+extern crate router_rs;
 extern crate virtual_dom_rs;
 
 pub mod server;
@@ -1,7 +1,10 @@
 extern crate actix_web;
+use self::actix_web::http::StatusCode;
 use self::actix_web::{fs, HttpRequest, HttpResponse, Responder};
 
-use isomorphic_app::App;
+use isomorphic_app::{App, Msg};
+use router_rs::Resolved;
+use virtual_dom_rs::RenderOptions;
 
 const HTML_PLACEHOLDER: &str = "#HTML_INSERTED_HERE_BY_SERVER#";
 const STATE_PLACEHOLDER: &str = "#INITIAL_STATE_JSON#";
@@ -13,13 +16,34 @@ fn index(req: &HttpRequest) -> impl Responder {
             .map(|string| string.parse().expect("bad param"))
             .unwrap_or(1001),
     );
+
+    let path = match req.query_string() {
+        "" => req.path().to_string(),
+        query => format!("{}?{}", req.path(), query),
+    };
+    app.store.borrow_mut().dispatch(Msg::Path(path));
+
+    let status = match app.resolve() {
+        Resolved::View(_) => StatusCode::OK,
+        Resolved::NotFound(_) => StatusCode::NOT_FOUND,
+        Resolved::Redirect(redirect) => {
+            let status = StatusCode::from_u16(redirect.status).unwrap_or(StatusCode::FOUND);
+
+            return HttpResponse::build(status)
+                .header("Location", redirect.path)
+                .finish();
+        }
+    };
+
     let state = app.store.borrow();
 
     let html = format!("{}", include_str!("./index.html"));
-    let html = html.replacen(HTML_PLACEHOLDER, &app.render().to_string(), 1);
+    let html = html.replacen(HTML_PLACEHOLDER, &app.render().to_html(&RenderOptions::minified()), 1);
     let html = html.replacen(STATE_PLACEHOLDER, &state.to_json(), 1);
 
-    HttpResponse::Ok().content_type("text/html").body(html)
+    HttpResponse::build(status)
+        .content_type("text/html")
+        .body(html)
 }
 
 pub fn serve() {
@@ -27,13 +51,21 @@ pub fn serve() {
         let app = actix_web::App::new();
         let app = app.resource("/", |r| r.f(index));
 
+        // Paths that aren't static files get rendered by our app's router
+
         // Development
         #[cfg(debug_assertions)]
-        let app = app.handler("/", fs::StaticFiles::new("./build").unwrap());
+        let app = app.handler(
+            "/",
+            fs::StaticFiles::new("./build").unwrap().default_handler(index),
+        );
 
         // Production
         #[cfg(not(debug_assertions))]
-        let app = app.handler("/", fs::StaticFiles::new("./dist").unwrap());
+        let app = app.handler(
+            "/",
+            fs::StaticFiles::new("./dist").unwrap().default_handler(index),
+        );
 
         app
     });
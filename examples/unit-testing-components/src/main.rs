@@ -1,5 +1,7 @@
 #![feature(proc_macro_hygiene)]
 
+use std::cell::Cell;
+use std::rc::Rc;
 use virtual_dom_rs::prelude::*;
 
 fn main() {
@@ -11,7 +13,7 @@ fn main() {
 fn full_water_bottle() -> VirtualNode {
     html! {
     <div>
-        <span label="full-water">
+        <span class="full-water">
           I am full of delicious and refreshing H20!
         </span>
     </div>
@@ -27,7 +29,7 @@ fn struggling_water_bottle(percent_full: f32) -> VirtualNode {
     let message = VirtualNode::text(&message);
 
     html! {
-        <div label="struggle-water">
+        <div class="struggle-water">
          { message }
         </div>
     }
@@ -42,6 +44,17 @@ fn water_bottle_view(percent_full: f32) -> VirtualNode {
     }
 }
 
+#[allow(unused)]
+fn refill_button(percent_full: Rc<Cell<f32>>) -> VirtualNode {
+    html! {
+        <div>
+          <button class="refill" onclick=move |_: u8| { percent_full.set(1.0) }>
+            Refill
+          </button>
+        </div>
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,7 +63,7 @@ mod tests {
     fn conditional_water_messaging() {
         assert_eq!(
             water_bottle_view(0.7)
-                .filter_label_equals("full-water")
+                .query_selector_all("div > span.full-water")
                 .len(),
             1
         );
@@ -68,4 +81,35 @@ mod tests {
             "Please fill me up :( I am only 0.2587 percent full :("
         )
     }
+
+    #[test]
+    fn full_water_bottle_markup() {
+        let expected = VirtualNode::from_html(
+            r#"
+            <div>
+                <span class="full-water">I am full of delicious and refreshing H20!</span>
+            </div>
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(full_water_bottle(), expected);
+    }
+
+    #[test]
+    fn struggling_water_bottle_snapshot() {
+        assert_snapshot!("struggling_water_bottle", struggling_water_bottle(0.25));
+    }
+
+    #[test]
+    fn refill_fills_the_water_bottle() {
+        let percent_full = Rc::new(Cell::new(0.2));
+
+        let view = refill_button(Rc::clone(&percent_full));
+        view.query_selector("button.refill")
+            .expect("Refill button")
+            .trigger_event("click", 0_u8);
+
+        assert_eq!(percent_full.get(), 1.0);
+    }
 }
@@ -0,0 +1,9 @@
+use css_rs_macro::css;
+
+pub fn button_class() -> &'static str {
+    css! {"
+    :host {
+        cursor: pointer;
+    }
+    "}
+}
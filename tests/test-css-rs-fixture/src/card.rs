@@ -0,0 +1,10 @@
+use css_rs_macro::css;
+
+/// The same CSS as the button, which still gets its own class since it's in another module
+pub fn card_class() -> &'static str {
+    css! {"
+    :host {
+        cursor: pointer;
+    }
+    "}
+}
@@ -4,11 +4,20 @@ extern crate css_rs_macro;
 
 use css_rs_macro::css;
 
+mod button;
+mod card;
+
 fn main() {
     let class1 = css! {r#"
     :host {
      color: red;
      background-color: blue;
+
+     > div {
+       display: flex;
+
+       &:hover { color: green; }
+     }
     }
     "#};
 
@@ -18,8 +27,13 @@ fn main() {
     }
     "};
 
-    // We need to use both of these variables so that the compiler doesn't optimize them away.
-    // If we didn't do this our test-css-rs integration test that builds this crate a would not
-    // be able to test that our CSS was written to disk.
-    assert_ne!(class1, class2);
+    // Our test-css-rs integration test runs this crate in order to know which class names to
+    // expect in the CSS that was written to disk.
+    println!(
+        "{} {} {} {}",
+        class1,
+        class2,
+        button::button_class(),
+        card::card_class()
+    );
 }
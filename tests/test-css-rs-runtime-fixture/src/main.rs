@@ -0,0 +1,38 @@
+#![feature(proc_macro_hygiene)]
+
+use css_rs::Css;
+use css_rs_macro::css;
+use virtual_dom_rs::prelude::*;
+
+static CARD_CSS: Css = css! {"
+:host {
+    display: flex;
+}
+"};
+
+static UNUSED_CSS: Css = css! {"
+:host {
+    color: blue;
+}
+"};
+
+fn main() {
+    let list = css! {module "
+    .list {
+        color: red;
+    }
+    .list-item {
+        color: green;
+    }
+    "};
+
+    let card = html! { <div class=CARD_CSS></div> };
+    let list = html! { <ul class=list.list><li class=list.list_item></li></ul> };
+
+    // Our test-css-rs integration test runs this crate in order to check the HTML and the
+    // <style> tags for the CSS that it used.
+    println!("{}", card);
+    println!("{}", list);
+    println!("{}", &*UNUSED_CSS);
+    println!("{}", css_rs::take_style_tags());
+}
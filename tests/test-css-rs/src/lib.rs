@@ -15,7 +15,7 @@ mod tests {
     use std::process::Command;
 
     #[test]
-    fn css_classes_increment() {
+    fn css_classes_are_hashed() {
         let class = css! {r#"
         :host {
             background-color: red;
@@ -25,12 +25,57 @@ mod tests {
         let class2 = css! {r#"
         :host {
             color: red;
+            > div { color: blue; }
         }
-        :host > div { color: blue; }
         "#};
 
-        assert_eq!(class, "_css_rs_0");
-        assert_eq!(class2, "_css_rs_1");
+        let same_css_as_class2 = css! {r#"
+        :host {
+            color: red;
+            > div { color: blue; }
+        }
+        "#};
+
+        assert!(class.starts_with("_css_rs_"));
+        assert_ne!(class, class2);
+        assert_eq!(class2, same_css_as_class2);
+    }
+
+    #[test]
+    fn local_classes_in_a_module() {
+        let classes = css! {module r#"
+        .list {
+            display: flex;
+            > .list-item:hover { color: blue; }
+        }
+        "#};
+
+        assert!(classes.list.starts_with("_css_rs_"));
+        assert!(classes.list.ends_with("_list"));
+        assert_eq!(classes.list_item, format!("{}-item", classes.list));
+    }
+
+    mod theme {
+        pub const PRIMARY: &'static str = "#2a38ef";
+    }
+
+    #[test]
+    fn rust_values_become_custom_properties() {
+        let gap = 20;
+
+        let card = css! {r#"
+        :host {
+            color: {theme::PRIMARY};
+            margin: {gap}px;
+            > div { padding: {gap}px; }
+        }
+        "#};
+
+        assert!(card.class.starts_with("_css_rs_"));
+        assert_eq!(
+            card.style,
+            format!("--{0}-0: #2a38ef; --{0}-1: 20;", card.class)
+        );
     }
 
     // TODO: Looks like this test will sometimes fail depending on if the test-css-rs-fixture crate
@@ -48,36 +93,140 @@ mod tests {
         css_rs_fixture.read_to_string(&mut contents).unwrap();
         fs::write(fixture, contents.as_bytes()).unwrap();
 
-        // Run cargo build and verify that our CSS gets extracted
+        // Run the fixture and verify that our CSS gets extracted. It prints its class names.
 
-        Command::new("cargo")
+        let output = Command::new("cargo")
             .env("OUTPUT_CSS", "/tmp/percy-test-css.css")
-            .arg("build")
-            .args(&["-p", "test-css-rs-fixture"])
-            .spawn()
-            .unwrap()
-            .wait()
+            .arg("run")
+            .args(&["-q", "-p", "test-css-rs-fixture"])
+            .output()
             .unwrap();
+        let classes = String::from_utf8(output.stdout).unwrap();
+        let classes = classes.split_whitespace().collect::<Vec<&str>>();
 
-        let mut file = File::open("/tmp/percy-test-css.css").unwrap();
-        let mut css = String::new();
-        file.read_to_string(&mut css).unwrap();
+        let strip = |css: &str| css.replace(" ", "").replace("\n", "");
+        let read = |path: &str| {
+            let mut file = File::open(path).unwrap();
+            let mut css = String::new();
+            file.read_to_string(&mut css).unwrap();
+            strip(&css)
+        };
 
-        assert_eq!(
-            css.replace(" ", "").replace("\n", ""),
+        let class1_css = format!(
             r#"
-        ._css_rs_0 {
+        .{0} {{
             color: red;
             background-color: blue;
-        }
-        ._css_rs_1 {
+        }}
+        .{0} > div {{
             display: flex;
+        }}
+        .{0} > div:hover {{
+            color: green;
+        }}
+        "#,
+            classes[0]
+        );
+        let class2_css = format!(".{} {{ display: flex; }}", classes[1]);
+        let button_css = format!(".{} {{ cursor: pointer; }}", classes[2]);
+        let card_css = format!(".{} {{ cursor: pointer; }}", classes[3]);
+
+        // The same CSS in another module gets its own class
+        assert_ne!(classes[2], classes[3]);
+
+        // Sorted by module and then by class, no matter what order they were written in
+        let mut main_css = vec![(classes[0], class1_css), (classes[1], class2_css)];
+        main_css.sort();
+        let main_css = main_css.into_iter().map(|(_, css)| css).collect::<String>();
+
+        assert_eq!(
+            read("/tmp/percy-test-css.css"),
+            strip(&format!("{}{}{}", main_css, button_css, card_css))
+        );
+
+        // Our fixture splits its CSS into a file per module
+        assert_eq!(
+            read("/tmp/percy-test-css.test_css_rs_fixture.css"),
+            strip(&main_css)
+        );
+        assert_eq!(
+            read("/tmp/percy-test-css.test_css_rs_fixture.button.css"),
+            strip(&button_css)
+        );
+        assert_eq!(
+            read("/tmp/percy-test-css.test_css_rs_fixture.card.css"),
+            strip(&card_css)
+        );
+
+        let mut sorted_classes = vec![classes[0], classes[1]];
+        sorted_classes.sort();
+        assert_eq!(
+            read("/tmp/percy-test-css.manifest.json"),
+            strip(&format!(
+                r#"{{
+                "test_css_rs_fixture": {{ "file": "percy-test-css.test_css_rs_fixture.css", "classes": ["{}", "{}"] }},
+                "test_css_rs_fixture::button": {{ "file": "percy-test-css.test_css_rs_fixture.button.css", "classes": ["{}"] }},
+                "test_css_rs_fixture::card": {{ "file": "percy-test-css.test_css_rs_fixture.card.css", "classes": ["{}"] }}
+                }}"#,
+                sorted_classes[0], sorted_classes[1], classes[2], classes[3]
+            ))
+        );
+
+        for file in &[
+            "/tmp/percy-test-css.css",
+            "/tmp/percy-test-css.test_css_rs_fixture.css",
+            "/tmp/percy-test-css.test_css_rs_fixture.button.css",
+            "/tmp/percy-test-css.test_css_rs_fixture.card.css",
+            "/tmp/percy-test-css.manifest.json",
+        ] {
+            fs::remove_file(file).unwrap();
         }
-        "#
-            .replace(" ", "")
-            .replace("\n", "")
+        fs::remove_dir_all("/tmp/percy-test-css.css.fragments").unwrap();
+    }
+
+    #[test]
+    fn runtime_style_tags() {
+        // The runtime fixture sets `runtime = true` in its Cargo.toml. It prints its HTML, a
+        // class that it never rendered and its <style> tags.
+        let output = Command::new("cargo")
+            .arg("run")
+            .args(&["-q", "-p", "test-css-rs-runtime-fixture"])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines = stdout.lines().collect::<Vec<&str>>();
+        assert!(
+            lines.len() > 3,
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let style_tags = lines[3..].join("\n");
+
+        let class_of = |html: &str| html.split('"').nth(1).unwrap().to_string();
+
+        let card = class_of(lines[0]);
+        assert_eq!(lines[0], format!(r#"<div class="{}"></div>"#, card));
+
+        let list = class_of(lines[1]);
+        assert!(list.ends_with("_list"));
+        let list_scope = &list[..list.len() - "_list".len()];
+        assert_eq!(
+            lines[1],
+            format!(r#"<ul class="{0}"><li class="{0}-item"></li></ul>"#, list)
+        );
+
+        let strip = |css: &str| css.replace(" ", "").replace("\n", "");
+        assert_eq!(
+            strip(&style_tags),
+            strip(&format!(
+                r#"<style data-css-rs="{0}">.{0} {{ display: flex; }}</style>
+                <style data-css-rs="{1}">.{1}_list {{ color: red; }} .{1}_list-item {{ color: green; }}</style>"#,
+                card, list_scope
+            ))
         );
 
-        fs::remove_file("/tmp/percy-test-css.css").unwrap();
+        // CSS that wasn't rendered doesn't get a <style> tag
+        assert!(lines[2].starts_with("_css_rs_"));
+        assert!(!style_tags.contains(lines[2]));
     }
 }